@@ -0,0 +1,125 @@
+// The app is a binary crate, so the benchmarks compile the modules they measure themselves
+#![allow(dead_code)]
+
+#[path = "../src/algorithms.rs"]
+mod algorithms;
+#[path = "../src/corpus.rs"]
+mod corpus;
+#[path = "../src/error.rs"]
+mod error;
+#[path = "../src/job/mod.rs"]
+mod job;
+#[path = "../src/solver/mod.rs"]
+mod solver;
+#[path = "../src/spatial_index.rs"]
+mod spatial_index;
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+
+// Points of every corpus case given to brute force, the first ones in grid order
+const BRUTE_FORCE_POINTS: usize = 12;
+
+// Photo centers of a square field flown with 10 x 15 m photos, rotated off the axes
+fn survey_grid(point_count: usize) -> Vec<(f64, f64)> {
+    let side = (point_count as f64).sqrt();
+    let field = vec![
+        (0.0, 0.0),
+        (side * 10.0, 0.0),
+        (side * 10.0, side * 15.0),
+        (0.0, side * 15.0),
+    ];
+    algorithms::discretize_area(vec![field], 10.0, 15.0, 30.0, true)
+        .unwrap()
+        .into_iter()
+        .flatten()
+        .flatten()
+        .collect()
+}
+
+fn discretize_area(c: &mut Criterion) {
+    let mut group = c.benchmark_group("discretize_area");
+    for case in corpus::cases() {
+        group.bench_with_input(BenchmarkId::from_parameter(case.name), &case, |b, case| {
+            b.iter(|| case.discretize())
+        });
+    }
+    group.finish();
+}
+
+fn nearest_neighbor(c: &mut Criterion) {
+    let mut group = c.benchmark_group("nearest_neighbor");
+    for case in corpus::cases() {
+        let points = case.photo_centers();
+        group.bench_with_input(
+            BenchmarkId::from_parameter(case.name),
+            &points,
+            |b, points| {
+                b.iter(|| algorithms::nearest_neighbor(points.clone(), case.start_point).unwrap())
+            },
+        );
+    }
+
+    group.sample_size(10);
+    for point_count in [10_000, 100_000] {
+        let points = survey_grid(point_count);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(point_count),
+            &points,
+            |b, points| {
+                b.iter_batched(
+                    || points.clone(),
+                    |points| algorithms::nearest_neighbor(points, (-50.0, -50.0)).unwrap(),
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+fn brute_force(c: &mut Criterion) {
+    let mut group = c.benchmark_group("brute_force");
+    group.sample_size(10);
+    for case in corpus::cases() {
+        let points: Vec<(f64, f64)> = case
+            .photo_centers()
+            .into_iter()
+            .take(BRUTE_FORCE_POINTS)
+            .collect();
+        group.bench_with_input(
+            BenchmarkId::from_parameter(case.name),
+            &points,
+            |b, points| {
+                b.iter(|| algorithms::brute_force(points.clone(), case.start_point).unwrap())
+            },
+        );
+    }
+    group.finish();
+}
+
+fn rectangular_areas(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rectangular_areas");
+    for case in corpus::cases() {
+        let grid = case.discretize();
+        group.bench_with_input(BenchmarkId::from_parameter(case.name), &grid, |b, grid| {
+            b.iter(|| {
+                algorithms::rectangular_areas(
+                    grid.clone(),
+                    case.start_point,
+                    case.direction_degrees,
+                )
+                .unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    discretize_area,
+    nearest_neighbor,
+    brute_force,
+    rectangular_areas
+);
+criterion_main!(benches);
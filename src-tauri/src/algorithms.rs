@@ -1,8 +1,12 @@
+use crate::error::{Error, Result};
+use crate::job::JobContext;
+use crate::spatial_index::KdTree;
+use serde::{Deserialize, Serialize};
 use std::f64::consts::PI;
 use std::f64::{INFINITY, NEG_INFINITY};
 
 // Coordinate transformation at rotation, they express old coordinates through new coordinates
-fn coordinate_restore(x: f64, y: f64, direction_radians: f64) -> (f64, f64) {
+pub(crate) fn coordinate_restore(x: f64, y: f64, direction_radians: f64) -> (f64, f64) {
     let cosinus = direction_radians.cos();
     let sinus = direction_radians.sin();
     let xd = x * cosinus - y * sinus;
@@ -19,7 +23,7 @@ fn y_restore(x: f64, y: f64, direction_radians: f64) -> f64 {
 }
 
 // Coordinate transformation at rotation, they express old coordinates through new coordinates
-fn coordinate_transformation(x: f64, y: f64, direction_radians: f64) -> (f64, f64) {
+pub(crate) fn coordinate_transformation(x: f64, y: f64, direction_radians: f64) -> (f64, f64) {
     let cosinus = direction_radians.cos();
     let sinus = direction_radians.sin();
     let xd = x * cosinus + y * sinus;
@@ -47,8 +51,8 @@ pub fn discretize_area(
     direction_degrees: f64,
     // Verification whether the points are inside the polygon
     check_inside: bool,
-) -> Result<Vec<Vec<Vec<(f64, f64)>>>, String> {
-    // Returns a Result containing either a vector of tuples representing the discretized area or a String error.
+) -> Result<Vec<Vec<Vec<(f64, f64)>>>> {
+    // Returns a Result containing either a vector of tuples representing the discretized area or an error.
     println!("Received polygon coordinates: {:?}", polygons);
 
     let direction_radians = direction_degrees * PI / 180.0;
@@ -151,39 +155,204 @@ pub fn discretize_area(
     Ok(results)
 }
 
+// Survey polygon with its own photo spacing and sweep direction
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SurveyRegion {
+    pub polygon: Vec<(f64, f64)>, // polygon in meters of the planning projection
+    pub photo_width: f64,         // spacing between columns in meters
+    pub photo_height: f64,        // spacing along a column in meters
+    pub direction_degrees: f64,   // discretization direction
+}
+
+// Discretize every region with its own settings, one grid per region as discretize_area
 #[tauri::command]
+pub fn discretize_regions(
+    regions: Vec<SurveyRegion>,
+    check_inside: bool,
+) -> Result<Vec<Vec<Vec<(f64, f64)>>>> {
+    let mut results = Vec::new();
+    for region in regions {
+        if region.photo_width <= 0.0 || region.photo_height <= 0.0 {
+            return Err(Error::validation("Photo size should be greater than zero"));
+        }
+        results.extend(discretize_area(
+            vec![region.polygon],
+            region.photo_width,
+            region.photo_height,
+            region.direction_degrees,
+            check_inside,
+        )?);
+    }
+    Ok(results)
+}
+
+#[tauri::command]
+pub fn discretize_corridor(
+    // Vector of tuples representing x and y coordinates of the corridor centerline.
+    centerline: Vec<(f64, f64)>,
+    // Full width of the corridor, centered on the centerline.
+    corridor_width: f64,
+    // Width of the photo, across the flight lines.
+    photo_width: f64,
+    // Height of the photo, along the flight lines.
+    photo_height: f64,
+) -> Result<Vec<Vec<(f64, f64)>>> {
+    // Returns a Result containing either a vector of flight lines with photo centers or an error.
+    if corridor_width <= 0.0 || photo_width <= 0.0 || photo_height <= 0.0 {
+        return Err(Error::validation(
+            "Corridor and photo sizes must be positive.",
+        ));
+    }
+
+    // Drop repeated vertices, they have no direction and break the offset calculation.
+    let mut centerline_clean: Vec<(f64, f64)> = Vec::new();
+    for point in centerline {
+        match centerline_clean.last() {
+            Some(last) if euclidean_distance(last, &point) < f64::EPSILON => (),
+            _ => centerline_clean.push(point),
+        }
+    }
+
+    if centerline_clean.len() < 2 {
+        return Err(Error::validation(
+            "The centerline must contain at least two distinct points.",
+        ));
+    }
+
+    // Number of flight lines needed to cover the corridor width, lines are placed
+    // symmetrically around the centerline.
+    let line_count = (corridor_width / photo_width).ceil().max(1.0) as u64;
+    let first_offset = -(line_count as f64 - 1.0) * photo_width / 2.0;
+
+    let mut result = Vec::new();
+
+    for i in 0..line_count {
+        let offset = first_offset + (i as f64) * photo_width;
+        let line = offset_polyline(&centerline_clean, offset);
+
+        // Place photo centers along the flight line in the same way as discretize_area
+        // places them along a column.
+        let line_length: f64 = line
+            .windows(2)
+            .map(|segment| euclidean_distance(&segment[0], &segment[1]))
+            .sum();
+        let photo_count = (line_length / photo_height).ceil().max(1.0) as u64;
+
+        let mut photo_centers = Vec::new();
+        for j in 0..photo_count {
+            let distance = photo_height / 2.0 + (j as f64) * photo_height;
+            photo_centers.push(point_along_polyline(&line, distance));
+        }
+        result.push(photo_centers);
+    }
+
+    Ok(result)
+}
+
+// Shift the polyline sideways by offset, positive values shift it to the left of the travel direction.
+// Vertices are moved along the bisector of adjacent segments so the lines keep a constant distance.
+fn offset_polyline(polyline: &[(f64, f64)], offset: f64) -> Vec<(f64, f64)> {
+    // Left unit normals of every segment.
+    let normals: Vec<(f64, f64)> = polyline
+        .windows(2)
+        .map(|segment| {
+            let length = euclidean_distance(&segment[0], &segment[1]);
+            (
+                -(segment[1].1 - segment[0].1) / length,
+                (segment[1].0 - segment[0].0) / length,
+            )
+        })
+        .collect();
+
+    let mut result = Vec::with_capacity(polyline.len());
+
+    for (i, &(x, y)) in polyline.iter().enumerate() {
+        let (nx, ny) = if i == 0 {
+            normals[0]
+        } else if i == polyline.len() - 1 {
+            normals[normals.len() - 1]
+        } else {
+            let (ax, ay) = normals[i - 1];
+            let (bx, by) = normals[i];
+            let (mx, my) = (ax + bx, ay + by);
+            let length = (mx * mx + my * my).sqrt();
+            if length < f64::EPSILON {
+                // The centerline turns back on itself, keep the incoming normal.
+                (ax, ay)
+            } else {
+                // Stretch the bisector so the offset is kept from both segments,
+                // the stretch is limited to avoid spikes on sharp turns.
+                let cosinus = ((mx * bx + my * by) / length).max(0.25);
+                (mx / length / cosinus, my / length / cosinus)
+            }
+        };
+        result.push((x + nx * offset, y + ny * offset));
+    }
+
+    result
+}
+
+// Point at the given distance along the polyline, past the end the last segment is extended.
+fn point_along_polyline(polyline: &[(f64, f64)], distance: f64) -> (f64, f64) {
+    let mut remaining = distance;
+
+    for (i, segment) in polyline.windows(2).enumerate() {
+        let length = euclidean_distance(&segment[0], &segment[1]);
+        let is_last = i == polyline.len() - 2;
+        if remaining <= length || is_last {
+            if length < f64::EPSILON {
+                return segment[1];
+            }
+            let ratio = remaining / length;
+            return (
+                segment[0].0 + (segment[1].0 - segment[0].0) * ratio,
+                segment[0].1 + (segment[1].1 - segment[0].1) * ratio,
+            );
+        }
+        remaining -= length;
+    }
+
+    polyline[polyline.len() - 1]
+}
+
+// Route without a job for tests and benchmarks, the app calculates routes with start_route_job
 pub fn nearest_neighbor(
     points: Vec<(f64, f64)>,
     start_point: (f64, f64),
-) -> Result<Vec<(f64, f64)>, String> {
+) -> Result<Vec<(f64, f64)>> {
+    nearest_neighbor_route(points, start_point, &JobContext::detached())
+}
+
+// Greedy route to the closest point left, the points are kept in a k-d tree so every step
+// costs about log n instead of a scan over all points.
+pub fn nearest_neighbor_route(
+    points: Vec<(f64, f64)>,
+    start_point: (f64, f64),
+    context: &JobContext,
+) -> Result<Vec<(f64, f64)>> {
     if points.is_empty() {
-        return Err("The input points must not be empty.".to_string());
+        return Err(Error::validation("The input points must not be empty."));
     }
 
-    let mut remaining_points: Vec<(f64, f64)> = points;
-    let mut result: Vec<(f64, f64)> = Vec::new();
+    let mut remaining_points = KdTree::new(&points);
+    let mut result: Vec<(f64, f64)> = Vec::with_capacity(points.len() + 2);
     let mut current_point = start_point;
+    result.push(start_point);
 
-    while !remaining_points.is_empty() {
-        let (nearest_index, nearest_point) = remaining_points
-            .iter()
-            .enumerate()
-            .min_by(|(_, a), (_, b)| {
-                euclidean_distance(&current_point, a)
-                    .partial_cmp(&euclidean_distance(&current_point, b))
-                    .unwrap_or(std::cmp::Ordering::Equal)
-            })
-            .ok_or("Failed to find the nearest point".to_string())?;
-        let nearest_point = *nearest_point;
+    for visited in 0..points.len() {
+        context.check_cancelled()?;
+        context.report(None, visited as f64 * 100.0 / points.len() as f64);
+
+        let nearest_index = remaining_points
+            .nearest(&current_point)
+            .ok_or_else(|| Error::algorithm("Failed to find the nearest point"))?;
         remaining_points.remove(nearest_index);
-        if result.is_empty() {
-            result.push(start_point);
-        }
-        result.push(nearest_point);
-        current_point = nearest_point;
+        current_point = points[nearest_index];
+        result.push(current_point);
     }
 
     result.push(start_point);
+    context.report(Some(calculate_distance(result.clone())?), 100.0);
     Ok(result)
 }
 
@@ -194,609 +363,1589 @@ pub fn euclidean_distance(a: &(f64, f64), b: &(f64, f64)) -> f64 {
     ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt()
 }
 
-use std::sync::{Arc, Mutex};
-use std::{thread, vec};
+// Distance from a point to the closest point of the segment a-b
+pub fn segment_distance(point: &(f64, f64), a: &(f64, f64), b: &(f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length_squared = dx * dx + dy * dy;
+    if length_squared == 0.0 {
+        return euclidean_distance(point, a);
+    }
+    let t = (((point.0 - a.0) * dx + (point.1 - a.1) * dy) / length_squared).clamp(0.0, 1.0);
+    euclidean_distance(point, &(a.0 + t * dx, a.1 + t * dy))
+}
 
-// Main function to find the shortest path using the brute force approach.
-#[tauri::command]
-pub fn brute_force(points: Vec<(f64, f64)>, start_point: (f64, f64)) -> Vec<(f64, f64)> {
-    // Wrap the points and best_path in Arc for shared ownership across threads.
-    let points = Arc::new(points);
-    let best_path = Arc::new(Mutex::new((Vec::new(), f64::MAX)));
+use crossbeam_deque::{Injector, Steal, Stealer, Worker};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
 
-    let mut threads = Vec::new();
+// Main function to find the shortest path using the brute force approach.
+pub fn brute_force(points: Vec<(f64, f64)>, start_point: (f64, f64)) -> Result<Vec<(f64, f64)>> {
+    brute_force_route(points, start_point, &JobContext::detached())
+}
 
-    // Iterate through each point, creating a separate thread for each.
-    for (i, point) in points.iter().enumerate() {
-        // Clone Arc references to share ownership with the spawned threads.
-        let points = Arc::clone(&points);
-        let best_path = Arc::clone(&best_path);
-        let start_point = start_point.clone();
-        let removed_point = point.clone();
-
-        // Spawn a thread for each point, removing it from the remaining points.
-        let thread = thread::spawn(move || {
-            let mut remaining_points = points.to_vec();
-            remaining_points.remove(i);
-
-            let current_path = vec![start_point, removed_point];
-            let current_distance = euclidean_distance(&start_point, &removed_point);
-
-            // Call the helper function in each thread.
-            brute_force_helper(
-                remaining_points,
-                current_path,
-                start_point,
-                current_distance,
-                &best_path,
-            );
-        });
+// Prefixes handed out per worker thread, more of them balance the pool better
+const PREFIXES_PER_WORKER: usize = 64;
+
+// Rounding slack in meters when comparing lower bounds against the shortest route
+const BOUND_TOLERANCE: f64 = 1e-9;
+
+// State shared by the brute force workers. Points are indexed, the start point is the
+// last index of the distance matrix.
+struct BruteForceSearch {
+    distances: Vec<Vec<f64>>, // distances between all points and the start point
+    shortest_edges: Vec<f64>, // shortest edge leaving every point, for the lower bound
+    start_order: Vec<usize>,  // points from the closest to the start point on
+    shortest: AtomicU64,      // bits of the shortest route length found by any worker
+    explored: AtomicU64,      // prefixes searched or pruned
+    prefix_count: u64,        // prefixes in total
+    context: JobContext,      // cancellation and progress of the job
+}
 
-        threads.push(thread);
+impl BruteForceSearch {
+    // Lengths are never negative, so their bits order the same way as the lengths
+    fn shortest(&self) -> f64 {
+        f64::from_bits(self.shortest.load(Ordering::Relaxed))
     }
 
-    // Wait for all threads to complete.
-    for thread in threads {
-        thread.join().unwrap();
+    fn offer(&self, length: f64) {
+        self.shortest.fetch_min(length.to_bits(), Ordering::Relaxed);
     }
+}
 
-    // Extract the best path from the Arc<Mutex<_>>.
-    let (best_path, _) = Arc::try_unwrap(best_path).unwrap().into_inner().unwrap();
-    best_path
+// Best route of one worker, as point indices without the start point
+struct BruteForceBest {
+    order: Vec<usize>,
+    length: f64,
 }
 
-// Recursive helper function to find the shortest path using the brute force approach.
-fn brute_force_helper(
+impl BruteForceBest {
+    // Equal lengths go to the smaller order, so the result is the same whatever worker
+    // finds which route first
+    fn offer(&mut self, order: &[usize], length: f64) -> bool {
+        if length < self.length || (length == self.length && order < &self.order[..]) {
+            self.order.clear();
+            self.order.extend_from_slice(order);
+            self.length = length;
+            return true;
+        }
+        false
+    }
+}
+
+// Exact shortest route through all points by branch and bound. The search tree is cut into
+// prefixes of the first few points, a fixed pool of workers takes them from a shared queue
+// and steals from each other when their own queue runs dry.
+pub fn brute_force_route(
     points: Vec<(f64, f64)>,
-    current_path: Vec<(f64, f64)>,
     start_point: (f64, f64),
-    current_distance: f64,
-    best_path: &Arc<Mutex<(Vec<(f64, f64)>, f64)>>,
-) {
-    // Base case: if there are no points left, update the best path if the current path is shorter.
+    context: &JobContext,
+) -> Result<Vec<(f64, f64)>> {
     if points.is_empty() {
-        let total_distance =
-            current_distance + euclidean_distance(&start_point, current_path.last().unwrap());
+        return Err(Error::validation("The input points must not be empty."));
+    }
 
-        let mut best_path = best_path.lock().unwrap();
+    let point_count = points.len();
+    let all_points: Vec<(f64, f64)> = points.iter().copied().chain([start_point]).collect();
+    let distances: Vec<Vec<f64>> = all_points
+        .iter()
+        .map(|a| {
+            all_points
+                .iter()
+                .map(|b| euclidean_distance(a, b))
+                .collect()
+        })
+        .collect();
+    let shortest_edges = (0..=point_count)
+        .map(|i| {
+            (0..=point_count)
+                .filter(|&j| j != i)
+                .map(|j| distances[i][j])
+                .fold(f64::INFINITY, f64::min)
+        })
+        .collect();
+
+    let mut start_order: Vec<usize> = (0..point_count).collect();
+    start_order.sort_by(|&a, &b| distances[a][point_count].total_cmp(&distances[b][point_count]));
+
+    let worker_count = thread::available_parallelism().map_or(1, |count| count.get());
+    let prefixes = brute_force_prefixes(point_count, worker_count * PREFIXES_PER_WORKER);
+    let search = Arc::new(BruteForceSearch {
+        distances,
+        shortest_edges,
+        start_order,
+        shortest: AtomicU64::new(f64::INFINITY.to_bits()),
+        explored: AtomicU64::new(0),
+        prefix_count: prefixes.len() as u64,
+        context: context.clone(),
+    });
+    context.report(None, 0.0);
+
+    let injector = Arc::new(Injector::new());
+    for prefix in prefixes {
+        injector.push(prefix);
+    }
+    let queues: Vec<Worker<Vec<usize>>> = (0..worker_count.min(search.prefix_count as usize))
+        .map(|_| Worker::new_lifo())
+        .collect();
+    let stealers: Arc<Vec<Stealer<Vec<usize>>>> =
+        Arc::new(queues.iter().map(|queue| queue.stealer()).collect());
 
-        if total_distance < best_path.1 {
-            best_path.0 = current_path.clone();
-            best_path.0.push(start_point);
-            best_path.1 = total_distance;
-        }
-    } else {
-        // Iterate through each remaining point, removing it from the list and updating the path.
-        for (i, point) in points.iter().enumerate() {
-            let mut remaining_points = points.clone();
-            let removed_point = remaining_points.remove(i);
-
-            let last_point = current_path.last().unwrap();
-            let new_distance = current_distance + euclidean_distance(last_point, &removed_point);
-
-            // If the updated path is shorter than the current best path, continue the search.
-            if new_distance < best_path.lock().unwrap().1 {
-                let mut new_path = current_path.clone();
-                new_path.push(*point);
-
-                brute_force_helper(
-                    remaining_points,
-                    new_path,
-                    start_point,
-                    new_distance,
-                    best_path,
+    let mut threads = Vec::new();
+    for queue in queues {
+        let search = Arc::clone(&search);
+        let injector = Arc::clone(&injector);
+        let stealers = Arc::clone(&stealers);
+
+        threads.push(thread::spawn(move || {
+            let mut best = BruteForceBest {
+                order: Vec::new(),
+                length: f64::INFINITY,
+            };
+            let mut visited = vec![false; point_count];
+            let mut path = Vec::with_capacity(point_count);
+
+            while let Some(prefix) = next_prefix(&queue, &injector, &stealers) {
+                if search.context.is_cancelled() {
+                    break;
+                }
+                search_prefix(&search, &prefix, &mut visited, &mut path, &mut best);
+
+                let explored = search.explored.fetch_add(1, Ordering::Relaxed) + 1;
+                let shortest = search.shortest();
+                search.context.report(
+                    Some(shortest).filter(|shortest| shortest.is_finite()),
+                    explored as f64 * 100.0 / search.prefix_count as f64,
                 );
             }
+            best
+        }));
+    }
+
+    let mut best = BruteForceBest {
+        order: Vec::new(),
+        length: f64::INFINITY,
+    };
+    for thread in threads {
+        let worker_best = thread
+            .join()
+            .map_err(|_| Error::algorithm("A brute force worker thread panicked."))?;
+        best.offer(&worker_best.order, worker_best.length);
+    }
+    context.check_cancelled()?;
+    if best.order.len() != point_count {
+        return Err(Error::algorithm("Brute force did not find a route."));
+    }
+
+    context.report(Some(best.length), 100.0);
+    let mut result = vec![start_point];
+    result.extend(best.order.iter().map(|&i| points[i]));
+    result.push(start_point);
+    Ok(result)
+}
+
+// All orders of the first points, deep enough to give every worker many of them
+fn brute_force_prefixes(point_count: usize, wanted: usize) -> Vec<Vec<usize>> {
+    let mut prefixes: Vec<Vec<usize>> = vec![Vec::new()];
+    while prefixes.len() < wanted && prefixes[0].len() < point_count {
+        prefixes = prefixes
+            .iter()
+            .flat_map(|prefix| {
+                (0..point_count)
+                    .filter(move |i| !prefix.contains(i))
+                    .map(move |i| prefix.iter().copied().chain([i]).collect())
+            })
+            .collect();
+    }
+    prefixes
+}
+
+// Own queue first, then a batch from the shared queue, then whatever other workers have left
+fn next_prefix(
+    queue: &Worker<Vec<usize>>,
+    injector: &Injector<Vec<usize>>,
+    stealers: &[Stealer<Vec<usize>>],
+) -> Option<Vec<usize>> {
+    queue.pop().or_else(|| {
+        std::iter::repeat_with(|| {
+            injector
+                .steal_batch_and_pop(queue)
+                .or_else(|| stealers.iter().map(|stealer| stealer.steal()).collect())
+        })
+        .find(|steal| !steal.is_retry())
+        .and_then(Steal::success)
+    })
+}
+
+// Search every route starting with the prefix. The path and visited flags are reused for
+// all prefixes of a worker.
+fn search_prefix(
+    search: &BruteForceSearch,
+    prefix: &[usize],
+    visited: &mut [bool],
+    path: &mut Vec<usize>,
+    best: &mut BruteForceBest,
+) {
+    let start = search.distances.len() - 1;
+    visited.iter_mut().for_each(|flag| *flag = false);
+    path.clear();
+
+    let mut length = 0.0;
+    let mut last = start;
+    for &point in prefix {
+        length += search.distances[last][point];
+        visited[point] = true;
+        path.push(point);
+        last = point;
+    }
+    let remaining_edges: f64 = (0..visited.len())
+        .filter(|&i| !visited[i])
+        .map(|i| search.shortest_edges[i])
+        .sum();
+
+    brute_force_helper(search, visited, path, length, remaining_edges, best);
+}
+
+// Recursive helper function to find the shortest path using the brute force approach.
+// The rest of the route enters every point still to visit along at least its shortest edge.
+// On top of that it leaves the last point of the path along at least its shortest edge,
+// and it comes back to the start point from at least as far as the closest point left,
+// which bounds the length of any route through this path.
+fn brute_force_helper(
+    search: &BruteForceSearch,
+    visited: &mut [bool],
+    path: &mut Vec<usize>,
+    length: f64,
+    remaining_edges: f64,
+    best: &mut BruteForceBest,
+) {
+    let start = search.distances.len() - 1;
+    let last = path.last().copied().unwrap_or(start);
+
+    // Base case: if there are no points left, close the route back to the start point.
+    if path.len() == visited.len() {
+        let total = length + search.distances[last][start];
+        if best.offer(path, total) {
+            search.offer(total);
         }
+        return;
+    }
+
+    let closest_return = search
+        .start_order
+        .iter()
+        .find(|&&point| !visited[point])
+        .map_or(f64::INFINITY, |&point| search.distances[point][start])
+        .min(search.distances[last][start]);
+    let lower_bound = length + remaining_edges + search.shortest_edges[last].max(closest_return);
+    if lower_bound > search.shortest().min(best.length) + BOUND_TOLERANCE
+        || search.context.is_cancelled()
+    {
+        return;
+    }
+
+    // Extend the path in place with every remaining point, undoing it afterwards.
+    for point in 0..visited.len() {
+        if visited[point] {
+            continue;
+        }
+        visited[point] = true;
+        path.push(point);
+        brute_force_helper(
+            search,
+            visited,
+            path,
+            length + search.distances[last][point],
+            remaining_edges - search.shortest_edges[point],
+            best,
+        );
+        path.pop();
+        visited[point] = false;
     }
 }
 
+// Estimated mission duration in seconds: climb to altitude, then fly the route at the
+// ground speed, which is the flight speed unless the camera limits it
+pub fn mission_duration(
+    route_length: f64,
+    altitude: f64,
+    ground_speed: f64,
+    takeoff_speed: f64,
+) -> f64 {
+    route_length / ground_speed + altitude / takeoff_speed
+}
+
 #[tauri::command]
-pub fn calculate_distance(points: Vec<(f64, f64)>) -> f64 {
-    points
+pub fn calculate_distance(points: Vec<(f64, f64)>) -> Result<f64> {
+    Ok(points
         .iter()
         .zip(points.iter().cycle().skip(1))
         .map(|(a, b)| euclidean_distance(a, b))
-        .sum()
+        .sum())
 }
 
-#[tauri::command]
 pub fn rectangular_areas(
     points: Vec<Vec<Vec<(f64, f64)>>>,
     start_point: (f64, f64),
     // Direction for quick calculation of polygon distance
     direction_degrees: f64,
-) -> Result<Vec<(f64, f64)>, String> {
+) -> Result<Vec<(f64, f64)>> {
+    let directions_degrees = vec![direction_degrees; points.len()];
+    rectangular_regions(points, start_point, directions_degrees)
+}
+
+// Same as rectangular_areas for regions discretized with their own direction each
+pub fn rectangular_regions(
+    points: Vec<Vec<Vec<(f64, f64)>>>,
+    start_point: (f64, f64),
+    directions_degrees: Vec<f64>,
+) -> Result<Vec<(f64, f64)>> {
+    rectangular_route(
+        points,
+        start_point,
+        directions_degrees,
+        &JobContext::detached(),
+    )
+}
+
+pub fn rectangular_route(
+    points: Vec<Vec<Vec<(f64, f64)>>>,
+    start_point: (f64, f64),
+    directions_degrees: Vec<f64>,
+    context: &JobContext,
+) -> Result<Vec<(f64, f64)>> {
     if points.is_empty() {
-        return Err("The input points must not be empty.".to_string());
+        return Err(Error::validation("The input points must not be empty."));
+    }
+    if directions_degrees.len() != points.len() {
+        return Err(Error::validation(
+            "Every region should have its own direction.",
+        ));
+    }
+    let directions_radians = directions_degrees
+        .iter()
+        .map(|direction| direction * PI / 180.0);
+    let regions: Vec<(Vec<Vec<(f64, f64)>>, f64)> =
+        points.into_iter().zip(directions_radians).collect();
+    context.report(None, 0.0);
+    let regions = clip_overlapping_regions(regions);
+    if regions.is_empty() {
+        return Err(Error::validation("The regions contain no points."));
     }
-    let direction_radians = direction_degrees * PI / 180.0;
-    let mut calculation_result: Vec<(f64, f64)> = Vec::new();
-    let mut multiple_region_result: Vec<Vec<(f64, f64)>> = Vec::new();
 
-    let region_count = points.len();
-    let mut weights: Vec<Vec<Option<(f64, Direction)>>> =
-        vec![vec![None; region_count]; region_count];
+    context.check_cancelled()?;
+    let sweeps: Vec<Vec<Sweep>> = regions
+        .iter()
+        .map(|(region, _)| region_sweeps(region))
+        .collect();
 
-    for i in 0..region_count - 1 {
-        let i_height = points[i][0].len();
-        let i_width = points[i].len();
-        for j in i + 1..region_count {
-            let j_height = points[j][0].len();
-            let j_width = points[j].len();
+    // Ordering the regions is the long part, it is reported as the second half
+    context.check_cancelled()?;
+    context.report(None, 50.0);
 
-            let (a, b) = (
-                (
-                    coordinate_transformation(
-                        points[i][0][i_height - 1].0,
-                        points[i][0][i_height - 1].1,
-                        direction_radians,
-                    ),
-                    coordinate_transformation(
-                        points[i][i_width - 1][0].0,
-                        points[i][i_width - 1][0].1,
-                        direction_radians,
-                    ),
-                ),
-                (
-                    coordinate_transformation(
-                        points[j][0][j_height - 1].0,
-                        points[j][0][j_height - 1].1,
-                        direction_radians,
-                    ),
-                    coordinate_transformation(
-                        points[j][j_width - 1][0].0,
-                        points[j][j_width - 1][0].1,
-                        direction_radians,
-                    ),
-                ),
-            );
+    let order = if sweeps.len() <= EXACT_REGION_LIMIT {
+        exact_region_order(&sweeps, start_point, context)?
+    } else {
+        let rectangles: Vec<Vec<(f64, f64)>> = regions
+            .iter()
+            .map(|(region, direction_radians)| region_rectangle(region, *direction_radians))
+            .collect();
+        let order = greedy_region_order(&rectangles, &sweeps, start_point);
+        improve_region_order(order, &sweeps, start_point, context)?
+    };
 
-            let ((a_left, a_top), (a_right, a_bottom)) = a;
-            let ((b_left, b_top), (b_right, b_bottom)) = b;
+    let mut result_vec: Vec<(f64, f64)> = vec![start_point];
+    for (region, sweep) in order {
+        result_vec.extend(sweeps[region][sweep].points.iter().copied());
+    }
+    result_vec.push(start_point);
 
-            for inner_point in [(b_left, b_top), (b_right, b_bottom), (b_left, b_bottom), (b_right, b_top)].into_iter() {
-                if inner_point.0 >= a_left
-                    && inner_point.0 <= a_right
-                    && inner_point.1 >= a_bottom
-                    && inner_point.1 <= a_top
-                {
-                    return Err(String::from("The rectangles are intersecting."));
-                }
-            }
+    context.check_cancelled()?;
+    context.report(Some(calculate_distance(result_vec.clone())?), 100.0);
+    Ok(result_vec)
+}
 
-            let (weight, direction) = rectangles_shortest_path(a, b);
+// Rounding slack in meters when testing points against photo footprints
+const CLIP_TOLERANCE: f64 = 1e-6;
+
+// Drop empty columns and regions, then remove the points of every region that fall inside
+// the photo footprint of a point kept in an earlier region, those are already photographed.
+// Regions drawn next to or over each other become disjoint, a fully covered region
+// disappears. Each region comes with the direction of its grid, the footprints may be
+// rotated against each other.
+fn clip_overlapping_regions(
+    regions: Vec<(Vec<Vec<(f64, f64)>>, f64)>,
+) -> Vec<(Vec<Vec<(f64, f64)>>, f64)> {
+    let mut clipped_regions: Vec<(Vec<Vec<(f64, f64)>>, f64)> = Vec::new();
+    let mut footprints: Vec<Footprints> = Vec::new();
+
+    for (region, direction_radians) in regions {
+        let spacing = grid_spacing(&region, direction_radians);
+        let clipped: Vec<Vec<(f64, f64)>> = region
+            .into_iter()
+            .map(|column| {
+                column
+                    .into_iter()
+                    .filter(|point| !footprints.iter().any(|photos| photos.cover(*point)))
+                    .collect::<Vec<(f64, f64)>>()
+            })
+            .filter(|column| !column.is_empty())
+            .collect();
 
-            weights[i][j] = Some((weight, direction.clone()));
-            weights[j][i] = Some((weight.abs(), direction.opposite().clone()));
+        if !clipped.is_empty() {
+            footprints.push(Footprints::new(&clipped, spacing, direction_radians));
+            clipped_regions.push((clipped, direction_radians));
         }
     }
+    clipped_regions
+}
 
-    // Route inside rectangle
-    for region_points in points.clone() {
-        // Check if the input vector is rectangular
-        let height = region_points[0].len();
-        let width = region_points.len();
+// Ground covered by the photos of one region, every photo center with the spacing around it
+struct Footprints {
+    direction_radians: f64,
+    half_size: (f64, f64),    // half the spacing across and along the columns
+    centers: Vec<(f64, f64)>, // photo centers in the rotated frame
+}
 
-        if region_points.iter().any(|row| row.len() != height) {
-            return Err(String::from("Input vector is not rectangular."));
+impl Footprints {
+    fn new(region: &[Vec<(f64, f64)>], spacing: (f64, f64), direction_radians: f64) -> Footprints {
+        Footprints {
+            direction_radians,
+            half_size: (spacing.0 / 2.0, spacing.1 / 2.0),
+            centers: region
+                .iter()
+                .flatten()
+                .map(|point| coordinate_transformation(point.0, point.1, direction_radians))
+                .collect(),
         }
+    }
 
-        if width < 2 {
-            multiple_region_result.push(region_points.into_iter().flatten().collect());
-            break;
-        }
+    fn cover(&self, point: (f64, f64)) -> bool {
+        let (x, y) = coordinate_transformation(point.0, point.1, self.direction_radians);
+        self.centers.iter().any(|center| {
+            (x - center.0).abs() <= self.half_size.0 + CLIP_TOLERANCE
+                && (y - center.1).abs() <= self.half_size.1 + CLIP_TOLERANCE
+        })
+    }
+}
 
-        let mut region_result: Vec<(f64, f64)> = Vec::new();
+// Photo spacing of a grid across and along its columns, the smallest gap between columns
+// and between points of a column. A single column or row has no gap in one direction,
+// its photos are taken as square then.
+fn grid_spacing(region: &[Vec<(f64, f64)>], direction_radians: f64) -> (f64, f64) {
+    let smallest_gap = |mut values: Vec<f64>| {
+        values.sort_by(|a, b| a.total_cmp(b));
+        values
+            .windows(2)
+            .map(|pair| pair[1] - pair[0])
+            .filter(|gap| *gap > CLIP_TOLERANCE)
+            .fold(f64::INFINITY, f64::min)
+    };
+    let transformed: Vec<Vec<(f64, f64)>> = region
+        .iter()
+        .map(|column| {
+            column
+                .iter()
+                .map(|point| coordinate_transformation(point.0, point.1, direction_radians))
+                .collect()
+        })
+        .collect();
+
+    let across = smallest_gap(transformed.iter().flatten().map(|point| point.0).collect());
+    let along = transformed
+        .iter()
+        .map(|column| smallest_gap(column.iter().map(|point| point.1).collect()))
+        .fold(f64::INFINITY, f64::min);
+    match (across.is_finite(), along.is_finite()) {
+        (true, true) => (across, along),
+        (true, false) => (across, across),
+        (false, true) => (along, along),
+        (false, false) => (0.0, 0.0),
+    }
+}
 
-        for i in 0..width {
-            region_result.push(region_points[i][0]);
-        }
+// Bounding rectangle of a region in the rotated frame, as ((left, top), (right, bottom))
+fn region_bounds(region: &[Vec<(f64, f64)>], direction_radians: f64) -> ((f64, f64), (f64, f64)) {
+    region.iter().flatten().fold(
+        (
+            (f64::INFINITY, f64::NEG_INFINITY),
+            (f64::NEG_INFINITY, f64::INFINITY),
+        ),
+        |((left, top), (right, bottom)), point| {
+            let (x, y) = coordinate_transformation(point.0, point.1, direction_radians);
+            ((left.min(x), top.max(y)), (right.max(x), bottom.min(y)))
+        },
+    )
+}
 
-        for j in 1..height {
-            region_result.push(region_points[width - 1][j]);
-        }
+// One way to fly through a region, entering at its first point and leaving at its last
+struct Sweep {
+    points: Vec<(f64, f64)>,
+    length: f64, // distance flown inside the region
+}
 
-        let ramps = (width - 2) / 2;
+impl Sweep {
+    fn new(points: Vec<(f64, f64)>) -> Sweep {
+        let length = points
+            .windows(2)
+            .map(|pair| euclidean_distance(&pair[0], &pair[1]))
+            .sum();
+        Sweep { points, length }
+    }
 
-        for n in 0..ramps {
-            for j in (1..height).rev() {
-                region_result.push(region_points[width - 2 - 2 * n][j]);
-            }
+    fn entry(&self) -> (f64, f64) {
+        self.points[0]
+    }
 
-            for j in 1..height {
-                region_result.push(region_points[width - 3 - 2 * n][j]);
-            }
-        }
+    fn exit(&self) -> (f64, f64) {
+        self.points[self.points.len() - 1]
+    }
+}
 
-        if width % 2 == 1 {
-            let coils = (height - 1) / 2;
+// Back and forth sweeps of a region starting from each of its corners, along the columns
+// and, when the grid is full, along the rows as well
+fn region_sweeps(region: &[Vec<(f64, f64)>]) -> Vec<Sweep> {
+    let height = region[0].len();
+    let mut line_sets: Vec<Vec<Vec<(f64, f64)>>> = vec![region.to_vec()];
+    if region.len() > 1 && height > 1 && region.iter().all(|column| column.len() == height) {
+        line_sets.push(
+            (0..height)
+                .map(|j| region.iter().map(|column| column[j]).collect())
+                .collect(),
+        );
+    }
 
-            for n in 0..coils {
-                region_result.push(region_points[1][height - 1 - n * 2]);
-                region_result.push(region_points[0][height - 1 - n * 2]);
-                region_result.push(region_points[0][height - 2 - n * 2]);
-                region_result.push(region_points[1][height - 2 - n * 2]);
+    let mut sweeps: Vec<Sweep> = Vec::new();
+    for lines in line_sets {
+        for reverse_lines in [false, true] {
+            for reverse_first in [false, true] {
+                let ordered: Vec<&Vec<(f64, f64)>> = if reverse_lines {
+                    lines.iter().rev().collect()
+                } else {
+                    lines.iter().collect()
+                };
+                let points: Vec<(f64, f64)> = ordered
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(i, line)| {
+                        if (i % 2 == 0) != reverse_first {
+                            line.to_vec()
+                        } else {
+                            line.iter().rev().copied().collect()
+                        }
+                    })
+                    .collect();
+                // Single lines and points give the same sweep several times
+                if !sweeps.iter().any(|sweep| sweep.points == points) {
+                    sweeps.push(Sweep::new(points));
+                }
             }
+        }
+    }
+    sweeps
+}
 
-            if height % 2 == 0 {
-                region_result.push(region_points[1][1]);
-                region_result.push(region_points[0][1]);
-            }
-        } else {
-            for j in (1..height).rev() {
-                region_result.push(region_points[0][j]);
-            }
+// Regions up to this count are ordered exactly, more go through local search
+const EXACT_REGION_LIMIT: usize = 10;
+
+// Shortest tour from the start point through every region and back, choosing the order
+// together with the sweep of each region. Held-Karp over the visited regions, the last
+// region and the sweep it was flown with.
+fn exact_region_order(
+    sweeps: &[Vec<Sweep>],
+    start_point: (f64, f64),
+    context: &JobContext,
+) -> Result<Vec<(usize, usize)>> {
+    let region_count = sweeps.len();
+    let states = 1 << region_count;
+
+    let mut cost: Vec<Vec<Vec<f64>>> = (0..states)
+        .map(|_| {
+            sweeps
+                .iter()
+                .map(|region| vec![f64::INFINITY; region.len()])
+                .collect()
+        })
+        .collect();
+    let mut parent: Vec<Vec<Vec<Option<(usize, usize)>>>> = (0..states)
+        .map(|_| {
+            sweeps
+                .iter()
+                .map(|region| vec![None; region.len()])
+                .collect()
+        })
+        .collect();
+
+    for (region, region_sweeps) in sweeps.iter().enumerate() {
+        for (index, sweep) in region_sweeps.iter().enumerate() {
+            cost[1 << region][region][index] =
+                euclidean_distance(&start_point, &sweep.entry()) + sweep.length;
         }
+    }
 
-        multiple_region_result.push(region_result)
-    }
-
-    let mst = boruvka_mst(weights.clone());
-    println!("points {:?}", points);
-    println!("start_point {:?}", start_point);
-    println!("weights {:?}", weights);
-    println!("mst {:?}", mst);
-    println!("multiple_region_result {:?}", multiple_region_result);
-
-    let mut result_vec: Vec<(f64, f64)> = multiple_region_result[0].clone();
-    let mut done: Vec<usize> = vec![];
-
-    let start_node = 0;
-    let mut visited = vec![false; mst.len()];
-    let mut stack = vec![start_node];
-    let mut done = vec![start_node];
-    while let Some(i) = stack.pop() {
-        if !visited[i] {
-            println!("Visiting node: {}", i);
-            for j in mst[i].clone() {
-                if done.iter().position(|&x| x == j).is_none() {
-                    println!("connecting {} to {}", i, j);
-                    match weights[i][j].clone() {
-                        Some(edge) => match edge.1 {
-                            Direction::U => {
-                                println!("Going up");
-                                let points2: Vec<(f64, f64)> =
-                                    points[j].iter().map(|row| row[0]).collect();
-
-                                let points1: Vec<(f64, f64)> = points[i]
-                                    .iter()
-                                    .filter_map(|row| row.last().copied())
-                                    .collect();
-
-                                let (p1, p2) = find_minimal_pair(&points1, &points2).unwrap();
-                                result_vec.insert_tuple_after_element(
-                                    multiple_region_result[j].clone(),
-                                    p2,
-                                    p1,
-                                )
-                            }
-                            Direction::D => {
-                                println!("Going down");
-                                let points2: Vec<(f64, f64)> = points[j]
-                                    .iter()
-                                    .filter_map(|row| row.last().copied())
-                                    .collect();
-                                let points1: Vec<(f64, f64)> =
-                                    points[i].iter().map(|row| row[0]).collect();
-                                let (p1, p2) = find_minimal_pair(&points1, &points2).unwrap();
-                                result_vec.insert_tuple_after_element(
-                                    multiple_region_result[j].clone(),
-                                    p2,
-                                    p1,
-                                )
-                            }
-                            Direction::L => {
-                                println!("Going Left");
-                                let points2: Vec<(f64, f64)> = points[j].last().unwrap().clone();
-                                let points1: Vec<(f64, f64)> = points[i][0].clone();
-                                let (p1, p2) = find_minimal_pair(&points1, &points2).unwrap();
-                                result_vec.insert_tuple_after_element(
-                                    multiple_region_result[j].clone(),
-                                    p2,
-                                    p1,
-                                )
-                            }
-                            Direction::R => {
-                                println!("Going Right");
-                                let points2: Vec<(f64, f64)> = points[j][0].clone();
-                                let points1: Vec<(f64, f64)> = points[i].last().unwrap().clone();
-                                let (p1, p2) = find_minimal_pair(&points1, &points2).unwrap();
-                                result_vec.insert_tuple_after_element(
-                                    multiple_region_result[j].clone(),
-                                    p2,
-                                    p1,
-                                )
-                            }
-                            Direction::UL => {
-                                println!("Going UL");
-                                result_vec.insert_tuple_after_element(
-                                    multiple_region_result[j].clone(),
-                                    points[j][points[j].len() - 1][0],
-                                    points[i][0][points[i][0].len() - 1],
-                                )
-                            }
-                            Direction::UR => {
-                                println!("Going UR");
-                                result_vec.insert_tuple_after_element(
-                                    multiple_region_result[j].clone(),
-                                    points[j][0][0],
-                                    points[i][points[i].len() - 1][points[i][0].len() - 1],
-                                )
-                            }
-                            Direction::DL => {
-                                println!("Going DL");
-                                result_vec.insert_tuple_after_element(
-                                    multiple_region_result[j].clone(),
-                                    points[j][points[j].len() - 1][points[j][0].len() - 1],
-                                    points[i][0][0],
-                                )
-                            }
-                            Direction::DR => {
-                                println!("Going DR");
-                                result_vec.insert_tuple_after_element(
-                                    multiple_region_result[j].clone(),
-                                    points[j][0][points[j][0].len() - 1],
-                                    points[i][points[i].len() - 1][0],
-                                )
-                            }
-                        },
-                        None => (),
+    for mask in 1..states {
+        context.check_cancelled()?;
+        for last in (0..region_count).filter(|last| mask & (1 << last) != 0) {
+            for (index, sweep) in sweeps[last].iter().enumerate() {
+                let current = cost[mask][last][index];
+                if !current.is_finite() {
+                    continue;
+                }
+                for next in (0..region_count).filter(|next| mask & (1 << next) == 0) {
+                    let next_mask = mask | (1 << next);
+                    for (next_index, next_sweep) in sweeps[next].iter().enumerate() {
+                        let next_cost = current
+                            + euclidean_distance(&sweep.exit(), &next_sweep.entry())
+                            + next_sweep.length;
+                        if next_cost < cost[next_mask][next][next_index] {
+                            cost[next_mask][next][next_index] = next_cost;
+                            parent[next_mask][next][next_index] = Some((last, index));
+                        }
                     }
-                    done.push(j);
                 }
             }
+        }
+    }
+
+    let full = states - 1;
+    let mut best: Option<(usize, usize)> = None;
+    let mut best_cost = f64::INFINITY;
+    for (region, region_sweeps) in sweeps.iter().enumerate() {
+        for (index, sweep) in region_sweeps.iter().enumerate() {
+            let total = cost[full][region][index] + euclidean_distance(&sweep.exit(), &start_point);
+            if total < best_cost {
+                best_cost = total;
+                best = Some((region, index));
+            }
+        }
+    }
+
+    let mut order: Vec<(usize, usize)> = Vec::new();
+    let mut mask = full;
+    while let Some((region, index)) = best {
+        order.push((region, index));
+        best = parent[mask][region][index];
+        mask &= !(1 << region);
+    }
+    order.reverse();
+    Ok(order)
+}
 
-            // Mark the current node as visited
-            visited[i] = true;
+// Best sweep of every region for a fixed visiting order, a shortest path through the sweeps
+// of consecutive regions. Returns the tour length and the chosen sweeps.
+fn assign_sweeps(
+    order: &[usize],
+    sweeps: &[Vec<Sweep>],
+    start_point: (f64, f64),
+) -> (f64, Vec<(usize, usize)>) {
+    let mut cost: Vec<Vec<f64>> = Vec::with_capacity(order.len());
+    let mut parent: Vec<Vec<usize>> = Vec::with_capacity(order.len());
+
+    for (position, &region) in order.iter().enumerate() {
+        let mut layer_cost = Vec::with_capacity(sweeps[region].len());
+        let mut layer_parent = Vec::with_capacity(sweeps[region].len());
+        for sweep in &sweeps[region] {
+            let (best_parent, best_cost) = if position == 0 {
+                (0, euclidean_distance(&start_point, &sweep.entry()))
+            } else {
+                let previous = order[position - 1];
+                sweeps[previous]
+                    .iter()
+                    .enumerate()
+                    .map(|(index, previous_sweep)| {
+                        (
+                            index,
+                            cost[position - 1][index]
+                                + euclidean_distance(&previous_sweep.exit(), &sweep.entry()),
+                        )
+                    })
+                    .min_by(|a, b| a.1.total_cmp(&b.1))
+                    .unwrap_or((0, f64::INFINITY))
+            };
+            layer_cost.push(best_cost + sweep.length);
+            layer_parent.push(best_parent);
+        }
+        cost.push(layer_cost);
+        parent.push(layer_parent);
+    }
 
-            // Push unvisited neighbors onto the stack
-            for &neighbor in &mst[i] {
-                if !visited[neighbor] {
-                    stack.push(neighbor);
+    let last = match order.last() {
+        Some(&last) => last,
+        None => return (0.0, Vec::new()),
+    };
+    let (mut index, total) = sweeps[last]
+        .iter()
+        .enumerate()
+        .map(|(index, sweep)| {
+            (
+                index,
+                cost[order.len() - 1][index] + euclidean_distance(&sweep.exit(), &start_point),
+            )
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap_or((0, f64::INFINITY));
+
+    let mut assigned = vec![(0, 0); order.len()];
+    for position in (0..order.len()).rev() {
+        assigned[position] = (order[position], index);
+        index = parent[position][index];
+    }
+    (total, assigned)
+}
+
+// Starting order for many regions: the region with the closest entry, then repeatedly the
+// region whose rectangle is nearest to the current one
+fn greedy_region_order(
+    rectangles: &[Vec<(f64, f64)>],
+    sweeps: &[Vec<Sweep>],
+    start_point: (f64, f64),
+) -> Vec<usize> {
+    let entry_distance = |region: usize| {
+        sweeps[region]
+            .iter()
+            .map(|sweep| euclidean_distance(&start_point, &sweep.entry()))
+            .fold(f64::INFINITY, f64::min)
+    };
+
+    let mut remaining: Vec<usize> = (0..rectangles.len()).collect();
+    let mut order: Vec<usize> = Vec::with_capacity(rectangles.len());
+    while !remaining.is_empty() {
+        let position = (0..remaining.len())
+            .min_by(|&a, &b| {
+                let distance = |region: usize| match order.last() {
+                    Some(&last) => convex_polygon_distance(&rectangles[last], &rectangles[region]),
+                    None => entry_distance(region),
+                };
+                distance(remaining[a]).total_cmp(&distance(remaining[b]))
+            })
+            .unwrap_or(0);
+        order.push(remaining.remove(position));
+    }
+    order
+}
+
+// Local search over the visiting order: reverse a stretch of regions or move one region
+// elsewhere, keeping any change that shortens the tour, until none does
+fn improve_region_order(
+    mut order: Vec<usize>,
+    sweeps: &[Vec<Sweep>],
+    start_point: (f64, f64),
+    context: &JobContext,
+) -> Result<Vec<(usize, usize)>> {
+    let (mut best_cost, mut best) = assign_sweeps(&order, sweeps, start_point);
+    let mut improved = true;
+
+    while improved {
+        improved = false;
+        for i in 0..order.len() {
+            context.check_cancelled()?;
+            for j in i + 1..order.len() {
+                let mut reversed = order.clone();
+                reversed[i..=j].reverse();
+
+                let mut moved = order.clone();
+                let region = moved.remove(i);
+                moved.insert(j, region);
+
+                for candidate in [reversed, moved] {
+                    let (cost, assigned) = assign_sweeps(&candidate, sweeps, start_point);
+                    if cost < best_cost - 1e-9 {
+                        best_cost = cost;
+                        best = assigned;
+                        order = candidate;
+                        improved = true;
+                    }
                 }
             }
         }
     }
-    result_vec.push(result_vec[0].clone());
+    Ok(best)
+}
+
+// Corners of a region's bounding rectangle, rotated back into map coordinates
+fn region_rectangle(region: &[Vec<(f64, f64)>], direction_radians: f64) -> Vec<(f64, f64)> {
+    let ((left, top), (right, bottom)) = region_bounds(region, direction_radians);
+    [(left, bottom), (right, bottom), (right, top), (left, top)]
+        .iter()
+        .map(|&(x, y)| coordinate_restore(x, y, direction_radians))
+        .collect()
+}
+
+// Smallest distance between two convex polygons given by their vertices in order, zero when
+// they touch or overlap. Rectangles at any rotation, segments and single points work too.
+pub fn convex_polygon_distance(a: &[(f64, f64)], b: &[(f64, f64)]) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return INFINITY;
+    }
+
+    let crossing = polygon_edges(a)
+        .any(|(p1, p2)| polygon_edges(b).any(|(q1, q2)| segments_intersect(p1, p2, q1, q2)));
+    if crossing || inside_convex_polygon(a[0], b) || inside_convex_polygon(b[0], a) {
+        return 0.0;
+    }
+
+    // Apart, so the closest pair has a vertex of one polygon on an edge of the other
+    let vertex_to_edges = |vertices: &[(f64, f64)], polygon: &[(f64, f64)]| {
+        vertices
+            .iter()
+            .flat_map(|vertex| {
+                polygon_edges(polygon).map(move |(p1, p2)| segment_distance(vertex, &p1, &p2))
+            })
+            .fold(INFINITY, f64::min)
+    };
+    vertex_to_edges(a, b).min(vertex_to_edges(b, a))
+}
+
+// Closed ring edges, a single point is an edge of zero length
+fn polygon_edges(polygon: &[(f64, f64)]) -> impl Iterator<Item = ((f64, f64), (f64, f64))> + '_ {
+    (0..polygon.len()).map(move |i| (polygon[i], polygon[(i + 1) % polygon.len()]))
+}
+
+// Twice the signed area of the triangle a, b, c, positive when counterclockwise
+fn cross(a: (f64, f64), b: (f64, f64), c: (f64, f64)) -> f64 {
+    (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)
+}
+
+fn segments_intersect(p1: (f64, f64), p2: (f64, f64), q1: (f64, f64), q2: (f64, f64)) -> bool {
+    let (d1, d2) = (cross(q1, q2, p1), cross(q1, q2, p2));
+    let (d3, d4) = (cross(p1, p2, q1), cross(p1, p2, q2));
+    if ((d1 > 0.0 && d2 < 0.0) || (d1 < 0.0 && d2 > 0.0))
+        && ((d3 > 0.0 && d4 < 0.0) || (d3 < 0.0 && d4 > 0.0))
+    {
+        return true;
+    }
+
+    // Touching or collinear, an end point lies on the other segment
+    segment_distance(&p1, &q1, &q2) == 0.0
+        || segment_distance(&p2, &q1, &q2) == 0.0
+        || segment_distance(&q1, &p1, &p2) == 0.0
+        || segment_distance(&q2, &p1, &p2) == 0.0
+}
+
+// Point inside or on a convex polygon of either orientation
+fn inside_convex_polygon(point: (f64, f64), polygon: &[(f64, f64)]) -> bool {
+    if polygon.len() < 3 {
+        return false;
+    }
+    let sides: Vec<f64> = polygon_edges(polygon)
+        .map(|(p1, p2)| cross(p1, p2, point))
+        .collect();
+    sides.iter().all(|&side| side >= 0.0) || sides.iter().all(|&side| side <= 0.0)
+}
+
+#[tauri::command]
+pub fn search_long_distance(points: Vec<(f64, f64)>, start_point: (f64, f64)) -> Result<f64> {
+    if points.is_empty() {
+        return Err(Error::validation("No points provided"));
+    }
+
+    let max_distance = points.iter()
+        .map(|&point| euclidean_distance(&start_point, &point))
+        .fold(f64::NEG_INFINITY, |max_dist, dist| f64::max(max_dist, dist));
+
+    Ok(max_distance) 
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::{self, CorpusCase};
+    use proptest::prelude::*;
+    use std::sync::atomic::AtomicBool;
+    use std::time::{Duration, Instant};
+
+    // Samples along every edge, the brute force distance is off by at most half a spacing
+    const BOUNDARY_SAMPLES: usize = 100;
+
+    fn rectangle(center: (f64, f64), half_size: (f64, f64), angle: f64) -> Vec<(f64, f64)> {
+        [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+            .iter()
+            .map(|&(sx, sy)| {
+                let (x, y) = coordinate_restore(sx * half_size.0, sy * half_size.1, angle);
+                (center.0 + x, center.1 + y)
+            })
+            .collect()
+    }
 
-    let mut closest = result_vec[0];
-    let mut min_distance = euclidean_distance(&start_point, &closest);
+    fn boundary_samples(polygon: &[(f64, f64)]) -> Vec<(f64, f64)> {
+        polygon_edges(polygon)
+            .flat_map(|(p1, p2)| {
+                (0..BOUNDARY_SAMPLES).map(move |i| {
+                    let t = i as f64 / BOUNDARY_SAMPLES as f64;
+                    (p1.0 + t * (p2.0 - p1.0), p1.1 + t * (p2.1 - p1.1))
+                })
+            })
+            .collect()
+    }
 
-    for &point in result_vec.iter().skip(1) {
-        let distance = euclidean_distance(&start_point, &point);
-        if distance < min_distance {
-            min_distance = distance;
-            closest = point;
+    fn longest_edge(polygon: &[(f64, f64)]) -> f64 {
+        polygon_edges(polygon)
+            .map(|(p1, p2)| euclidean_distance(&p1, &p2))
+            .fold(0.0, f64::max)
+    }
+
+    // Minimum over boundary samples, zero when one polygon holds the other
+    fn brute_force_distance(a: &[(f64, f64)], b: &[(f64, f64)]) -> f64 {
+        if inside_convex_polygon(a[0], b) || inside_convex_polygon(b[0], a) {
+            return 0.0;
         }
+        let b_samples = boundary_samples(b);
+        boundary_samples(a)
+            .iter()
+            .flat_map(|p| b_samples.iter().map(move |q| euclidean_distance(p, q)))
+            .fold(INFINITY, f64::min)
     }
 
-    result_vec.insert(
-        result_vec.iter().position(|&x| x == closest).unwrap(),
-        start_point,
-    );
-    println!("res: {:?}", result_vec);
-
-    trait InsertTupleAfterElement {
-        fn insert_tuple_after_element(
-            &mut self,
-            vec2: Vec<(f64, f64)>,
-            element: (f64, f64),
-            at_element: (f64, f64),
+    fn assert_matches_brute_force(a: &[(f64, f64)], b: &[(f64, f64)]) {
+        let distance = convex_polygon_distance(a, b);
+        let brute_force = brute_force_distance(a, b);
+        let tolerance = (longest_edge(a) + longest_edge(b)) / BOUNDARY_SAMPLES as f64 + 1e-9;
+
+        assert!(distance >= 0.0);
+        assert!(
+            distance <= brute_force + 1e-9,
+            "{} above brute force {}",
+            distance,
+            brute_force
+        );
+        assert!(
+            brute_force - distance <= tolerance,
+            "{} below brute force {} by more than {}",
+            distance,
+            brute_force,
+            tolerance
         );
     }
 
-    impl InsertTupleAfterElement for Vec<(f64, f64)> {
-        fn insert_tuple_after_element(
-            &mut self,
-            vec2: Vec<(f64, f64)>,
-            element: (f64, f64),
-            at_element: (f64, f64),
+    fn rectangle_strategy() -> impl Strategy<Value = Vec<(f64, f64)>> {
+        (
+            (-100.0..100.0, -100.0..100.0),
+            (0.1..40.0, 0.1..40.0),
+            0.0..PI,
+        )
+            .prop_map(|(center, half_size, angle)| rectangle(center, half_size, angle))
+    }
+
+    // Vertices on a circle in angle order always form a convex polygon
+    fn convex_polygon_strategy() -> impl Strategy<Value = Vec<(f64, f64)>> {
+        (
+            (-100.0..100.0, -100.0..100.0),
+            1.0..40.0,
+            prop::collection::vec(0.0..2.0 * PI, 3..8),
+        )
+            .prop_map(|((cx, cy), radius, mut angles)| {
+                angles.sort_by(|a, b| a.total_cmp(b));
+                angles
+                    .iter()
+                    .map(|angle| (cx + radius * angle.cos(), cy + radius * angle.sin()))
+                    .collect()
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn rotated_rectangle_distance_matches_brute_force(
+            a in rectangle_strategy(),
+            b in rectangle_strategy(),
         ) {
-            let index = vec2.iter().position(|&x| x == element).unwrap();
-            let index_insert = self.iter().position(|&x| x == at_element).unwrap() + 1;
-            let (left, right) = vec2.split_at(index);
-            self.splice(index_insert..index_insert, right.to_vec());
-            self.splice(
-                index_insert + right.len()..index_insert + right.len(),
-                left.to_vec(),
-            );
+            assert_matches_brute_force(&a, &b);
+        }
+
+        #[test]
+        fn convex_polygon_distance_matches_brute_force(
+            a in convex_polygon_strategy(),
+            b in convex_polygon_strategy(),
+        ) {
+            assert_matches_brute_force(&a, &b);
+        }
+
+        #[test]
+        fn convex_polygon_distance_is_symmetric(
+            a in convex_polygon_strategy(),
+            b in rectangle_strategy(),
+        ) {
+            let forward = convex_polygon_distance(&a, &b);
+            let backward = convex_polygon_distance(&b, &a);
+            prop_assert!((forward - backward).abs() < 1e-9);
         }
     }
 
-    Ok(result_vec)
-}
+    // Sample points per photo width when measuring coverage
+    const COVERAGE_SAMPLES: f64 = 5.0;
 
-#[derive(Debug, Clone)]
-enum Direction {
-    U,
-    D,
-    L,
-    R,
-    UL,
-    UR,
-    DL,
-    DR,
-}
+    fn sorted(points: &[(f64, f64)]) -> Vec<(f64, f64)> {
+        let mut points = points.to_vec();
+        points.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+        points
+    }
 
-impl Direction {
-    pub fn opposite(&self) -> Direction {
-        match self {
-            Direction::U => Direction::D,
-            Direction::D => Direction::U,
-            Direction::L => Direction::R,
-            Direction::R => Direction::L,
-            Direction::UL => Direction::DR,
-            Direction::UR => Direction::DL,
-            Direction::DL => Direction::UR,
-            Direction::DR => Direction::UL,
+    fn assert_visits_each_once(
+        route: &[(f64, f64)],
+        start_point: (f64, f64),
+        centers: &[(f64, f64)],
+    ) {
+        assert_eq!(
+            route.first(),
+            Some(&start_point),
+            "route does not start at the start point"
+        );
+        assert_eq!(
+            route.last(),
+            Some(&start_point),
+            "route does not end at the start point"
+        );
+        assert_eq!(
+            sorted(&route[1..route.len() - 1]),
+            sorted(centers),
+            "route does not visit every photo center exactly once"
+        );
+    }
+
+    fn inside_polygon(point: (f64, f64), polygon: &[(f64, f64)]) -> bool {
+        polygon_edges(polygon)
+            .filter(|(a, b)| {
+                (a.1 > point.1) != (b.1 > point.1)
+                    && point.0 < (b.0 - a.0) * (point.1 - a.1) / (b.1 - a.1) + a.0
+            })
+            .count()
+            % 2
+            == 1
+    }
+
+    // Share of lattice samples inside the polygons that some photo footprint holds
+    fn coverage(case: &CorpusCase, centers: &[(f64, f64)]) -> f64 {
+        let direction_radians = case.direction_degrees * PI / 180.0;
+        let footprints: Vec<(f64, f64)> = centers
+            .iter()
+            .map(|&(x, y)| coordinate_transformation(x, y, direction_radians))
+            .collect();
+        let step = case.photo_width.min(case.photo_height) / COVERAGE_SAMPLES;
+
+        let (mut samples, mut covered) = (0, 0);
+        for polygon in &case.polygons {
+            let (min_x, max_x) = polygon
+                .iter()
+                .fold((INFINITY, NEG_INFINITY), |(min, max), p| {
+                    (min.min(p.0), max.max(p.0))
+                });
+            let (min_y, max_y) = polygon
+                .iter()
+                .fold((INFINITY, NEG_INFINITY), |(min, max), p| {
+                    (min.min(p.1), max.max(p.1))
+                });
+            let columns = ((max_x - min_x) / step) as usize;
+            let rows = ((max_y - min_y) / step) as usize;
+            for column in 0..=columns {
+                for row in 0..=rows {
+                    let sample = (
+                        min_x + (column as f64 + 0.5) * step,
+                        min_y + (row as f64 + 0.5) * step,
+                    );
+                    if !inside_polygon(sample, polygon) {
+                        continue;
+                    }
+                    samples += 1;
+                    let (x, y) = coordinate_transformation(sample.0, sample.1, direction_radians);
+                    if footprints.iter().any(|center| {
+                        (x - center.0).abs() <= case.photo_width / 2.0
+                            && (y - center.1).abs() <= case.photo_height / 2.0
+                    }) {
+                        covered += 1;
+                    }
+                }
+            }
         }
+        covered as f64 / samples as f64
     }
-}
 
-pub fn find_minimal_pair(
-    points1: &Vec<(f64, f64)>,
-    points2: &Vec<(f64, f64)>,
-) -> Option<((f64, f64), (f64, f64))> {
-    if points1.is_empty() || points2.is_empty() {
-        return None; // If either of the vectors is empty, there's no pair to compare
+    // Both route edges at a photo center are at least as long as the way to its closest
+    // neighbor, so no route through all centers is shorter than the sum of those
+    fn shortest_route_bound(centers: &[(f64, f64)], start_point: (f64, f64)) -> f64 {
+        centers
+            .iter()
+            .enumerate()
+            .map(|(i, center)| {
+                centers
+                    .iter()
+                    .enumerate()
+                    .filter(|&(j, _)| j != i)
+                    .map(|(_, other)| other)
+                    .chain([&start_point])
+                    .map(|other| euclidean_distance(center, other))
+                    .fold(INFINITY, f64::min)
+            })
+            .sum()
     }
 
-    let mut min_distance = std::f64::INFINITY;
-    let mut min_pair = None;
+    #[test]
+    fn corpus_discretization_stays_in_bounds() {
+        for case in corpus::cases() {
+            let centers = case.photo_centers();
+            let (fewest, most) = case.photo_count;
+            assert!(
+                (fewest..=most).contains(&centers.len()),
+                "{}: {} photo centers, expected {} to {}",
+                case.name,
+                centers.len(),
+                fewest,
+                most
+            );
 
-    for point1 in points1 {
-        for point2 in points2 {
-            let distance = euclidean_distance(point1, point2);
-            if distance < min_distance {
-                min_distance = distance;
-                min_pair = Some((*point1, *point2));
+            let coverage = coverage(&case, &centers);
+            assert!(
+                coverage >= case.min_coverage,
+                "{}: coverage {:.4} below {}",
+                case.name,
+                coverage,
+                case.min_coverage
+            );
+        }
+    }
+
+    #[test]
+    fn corpus_routes_visit_every_photo_center_once() {
+        for case in corpus::cases() {
+            let grid = case.discretize();
+            let centers = case.photo_centers();
+            let bound = shortest_route_bound(&centers, case.start_point);
+
+            let mut routes = vec![
+                (
+                    "nearest neighbor",
+                    nearest_neighbor(centers.clone(), case.start_point).unwrap(),
+                ),
+                (
+                    "rectangular areas",
+                    rectangular_areas(grid, case.start_point, case.direction_degrees).unwrap(),
+                ),
+            ];
+            if centers.len() <= corpus::BRUTE_FORCE_LIMIT {
+                routes.push((
+                    "brute force",
+                    brute_force(centers.clone(), case.start_point).unwrap(),
+                ));
+            }
+
+            for (algorithm, route) in &routes {
+                assert_visits_each_once(route, case.start_point, &centers);
+                let length = calculate_distance(route.clone()).unwrap();
+                assert!(
+                    length >= bound - 1e-6 && length <= case.max_route_length,
+                    "{} with {}: length {:.1} outside {:.1} to {:.1}",
+                    case.name,
+                    algorithm,
+                    length,
+                    bound,
+                    case.max_route_length
+                );
             }
         }
     }
 
-    min_pair
-}
+    #[test]
+    fn brute_force_is_never_longer_than_the_heuristics() {
+        for case in corpus::cases() {
+            let centers = case.photo_centers();
+            if centers.len() > corpus::BRUTE_FORCE_LIMIT {
+                continue;
+            }
+            let exact = brute_force(centers.clone(), case.start_point).unwrap();
+            let greedy = nearest_neighbor(centers, case.start_point).unwrap();
+            assert!(
+                calculate_distance(exact).unwrap() <= calculate_distance(greedy).unwrap() + 1e-9,
+                "{}: brute force longer than nearest neighbor",
+                case.name
+            );
+        }
+    }
 
-fn find_direction(a: ((f64, f64), (f64, f64)), b: ((f64, f64), (f64, f64))) -> Direction {
-    let ((a_left, a_top), (a_right, a_bottom)) = a;
-    let ((b_left, b_top), (b_right, b_bottom)) = b;
-    let direction = if b_right < a_left {
-        if a_top < b_bottom {
-            Direction::UL
-        } else if b_top < a_bottom {
-            Direction::DL
-        } else {
-            Direction::L
+    // Star shaped around its center, concave wherever the radius dips
+    fn field_strategy() -> impl Strategy<Value = Vec<(f64, f64)>> {
+        (
+            (-500.0..500.0, -500.0..500.0),
+            prop::collection::vec((30.0..150.0, -0.2..0.2), 4..12),
+        )
+            .prop_map(|((cx, cy), vertices): ((f64, f64), Vec<(f64, f64)>)| {
+                let count = vertices.len() as f64;
+                vertices
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &(radius, jitter))| {
+                        let angle = (i as f64 + 0.5 + jitter) * 2.0 * PI / count;
+                        (cx + radius * angle.cos(), cy + radius * angle.sin())
+                    })
+                    .collect()
+            })
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn routes_visit_every_photo_center_once(
+            field in field_strategy(),
+            second_part in prop::option::of(field_strategy()),
+            photo_size in (15.0..40.0, 15.0..40.0),
+            direction_degrees in 0.0..180.0,
+            start_point in (-800.0..800.0, -800.0..800.0),
+        ) {
+            // A second part far enough away that the region rectangles never overlap
+            let mut polygons = vec![field];
+            polygons.extend(second_part.map(|part| {
+                part.iter().map(|&(x, y)| (x + 2000.0, y)).collect::<Vec<_>>()
+            }));
+            let grid = discretize_area(
+                polygons,
+                photo_size.0,
+                photo_size.1,
+                direction_degrees,
+                true,
+            )
+            .unwrap();
+            let centers: Vec<(f64, f64)> = grid.iter().flatten().flatten().copied().collect();
+            prop_assume!(!centers.is_empty());
+
+            let route = nearest_neighbor(centers.clone(), start_point).unwrap();
+            assert_visits_each_once(&route, start_point, &centers);
+            let route = rectangular_areas(grid, start_point, direction_degrees).unwrap();
+            assert_visits_each_once(&route, start_point, &centers);
         }
-    } else if a_right < b_left {
-        if a_top < b_bottom {
-            Direction::UR
-        } else if b_top < a_bottom {
-            Direction::DR
-        } else {
-            Direction::R
+
+        #[test]
+        fn brute_force_visits_every_point_once(
+            points in prop::collection::vec((-100.0..100.0, -100.0..100.0), 1..7),
+            start_point in (-100.0..100.0, -100.0..100.0),
+        ) {
+            let route = brute_force(points.clone(), start_point).unwrap();
+            assert_visits_each_once(&route, start_point, &points);
         }
-    } else {
-        if a_top < b_bottom {
-            Direction::U
-        } else {
-            Direction::D
+
+        #[test]
+        fn brute_force_finds_the_shortest_permutation(
+            points in prop::collection::vec((-100.0..100.0, -100.0..100.0), 1..7),
+            start_point in (-100.0..100.0, -100.0..100.0),
+        ) {
+            let route = brute_force(points.clone(), start_point).unwrap();
+            let shortest = shortest_permutation(&points, start_point);
+            let length = calculate_distance(route).unwrap();
+            assert!((length - shortest).abs() < 1e-6, "{} instead of {}", length, shortest);
         }
-    };
-    direction
-}
-
-// Method to calculate the shortest path between two rectangles
-fn rectangles_shortest_path(
-    a: ((f64, f64), (f64, f64)),
-    b: ((f64, f64), (f64, f64)),
-) -> (f64, Direction) {
-    let direction = find_direction(a, b);
-
-    let ((a_left, a_top), (a_right, a_bottom)) = a;
-    let ((b_left, b_top), (b_right, b_bottom)) = b;
-
-    (
-        match direction {
-            Direction::U => b_bottom - a_top,
-            Direction::D => a_bottom - b_top,
-            Direction::L => a_left - b_right,
-            Direction::R => b_left - a_right,
-            // 0011 1011
-            // 0001 1001
-            Direction::UL => euclidean_distance(&(a_top, a_left), &(b_bottom, b_right)),
-            Direction::UR => euclidean_distance(&(a_top, a_right), &(b_bottom, b_left)),
-            Direction::DL => euclidean_distance(&(a_bottom, a_left), &(b_top, b_right)),
-            Direction::DR => euclidean_distance(&(a_bottom, a_right), &(b_top, b_left)),
-        },
-        direction,
-    )
-}
+    }
 
-fn find(parent: &mut [usize], i: usize) -> usize {
-    if parent[i] == i {
-        i
-    } else {
-        parent[i] = find(parent, parent[i]);
-        parent[i]
+    // Length of the shortest closed route from the start point, trying every order
+    fn shortest_permutation(points: &[(f64, f64)], start_point: (f64, f64)) -> f64 {
+        fn extend(
+            last: (f64, f64),
+            remaining: &mut Vec<(f64, f64)>,
+            start_point: (f64, f64),
+        ) -> f64 {
+            if remaining.is_empty() {
+                return euclidean_distance(&last, &start_point);
+            }
+            let mut shortest = f64::INFINITY;
+            for i in 0..remaining.len() {
+                let next = remaining.remove(i);
+                let length =
+                    euclidean_distance(&last, &next) + extend(next, remaining, start_point);
+                shortest = shortest.min(length);
+                remaining.insert(i, next);
+            }
+            shortest
+        }
+        extend(start_point, &mut points.to_vec(), start_point)
     }
-}
 
-fn union_set(parent: &mut [usize], rank: &mut [usize], x: usize, y: usize) {
-    let xroot = find(parent, x);
-    let yroot = find(parent, y);
+    #[test]
+    fn brute_force_workers_stop_when_cancelled() {
+        // Far too many points to finish, only cancelling ends the search
+        let points: Vec<(f64, f64)> = (0..40)
+            .map(|i| ((i % 8) as f64 * 10.0, (i / 8) as f64 * 10.0))
+            .collect();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let context = JobContext::stoppable(Arc::clone(&cancelled));
+        let canceller = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            cancelled.store(true, Ordering::Relaxed);
+        });
 
-    if rank[xroot] < rank[yroot] {
-        parent[xroot] = yroot;
-    } else if rank[xroot] > rank[yroot] {
-        parent[yroot] = xroot;
-    } else {
-        parent[yroot] = xroot;
-        rank[xroot] += 1;
+        let started = Instant::now();
+        let result = brute_force_route(points, (-10.0, -10.0), &context);
+        canceller.join().unwrap();
+        assert!(matches!(result, Err(Error::Cancelled(_))));
+        assert!(started.elapsed() < Duration::from_secs(5));
     }
-}
 
-fn boruvka_mst(weights: Vec<Vec<Option<(f64, Direction)>>>) -> Vec<Vec<usize>> {
-    let mut parent: Vec<usize> = (0..weights.len()).collect();
-    let mut rank: Vec<usize> = vec![0; weights.len()];
-    let mut cheapest: Vec<Option<(usize, usize, f64)>> = vec![None; weights.len()];
-    let mut num_trees = weights.len();
-    let mut mst_weight = 0.0;
+    #[test]
+    fn diagonal_rectangles_use_corner_distance() {
+        let a = rectangle((0.0, 0.0), (10.0, 5.0), 0.0);
+        let b = rectangle((40.0, 30.0), (10.0, 5.0), 0.0);
+        // Closest corners (10, 5) and (30, 25)
+        assert!((convex_polygon_distance(&a, &b) - 800f64.sqrt()).abs() < 1e-9);
+    }
 
-    let mut result = vec![vec![]; weights.len()];
+    #[test]
+    fn touching_and_nested_polygons_have_no_distance() {
+        let a = rectangle((0.0, 0.0), (10.0, 10.0), 0.0);
+        let beside = rectangle((20.0, 0.0), (10.0, 10.0), 0.0);
+        let nested = rectangle((0.0, 0.0), (2.0, 2.0), 0.7);
+        assert_eq!(convex_polygon_distance(&a, &beside), 0.0);
+        assert_eq!(convex_polygon_distance(&a, &nested), 0.0);
+    }
 
-    while num_trees > 1 {
-        for u in 0..weights.len() {
-            for v in 0..weights[u].len() {
-                if let Some((w, _)) = weights[u][v] {
-                    let set1 = find(&mut parent, u);
-                    let set2 = find(&mut parent, v);
-                    if set1 != set2 {
-                        if cheapest[set1].is_none() || cheapest[set1].unwrap().2 > w {
-                            cheapest[set1] = Some((u, v, w));
-                        }
-                        if cheapest[set2].is_none() || cheapest[set2].unwrap().2 > w {
-                            cheapest[set2] = Some((u, v, w));
-                        }
-                    }
-                }
-            }
+    fn polyline_distance(point: &(f64, f64), polyline: &[(f64, f64)]) -> f64 {
+        polyline
+            .windows(2)
+            .map(|segment| segment_distance(point, &segment[0], &segment[1]))
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    #[test]
+    fn corridor_lines_cover_a_straight_centerline() {
+        let lines = discretize_corridor(vec![(0.0, 0.0), (100.0, 0.0)], 50.0, 20.0, 30.0).unwrap();
+
+        // Three lines 20 m apart centered on the centerline, four photos along 100 m
+        assert_eq!(lines.len(), 3);
+        for (line, y) in lines.iter().zip([-20.0, 0.0, 20.0]) {
+            let xs: Vec<f64> = line.iter().map(|point| point.0).collect();
+            assert_eq!(xs, vec![15.0, 45.0, 75.0, 105.0]);
+            assert!(line.iter().all(|point| (point.1 - y).abs() < 1e-9));
         }
+    }
 
-        for node in 0..weights.len() {
-            if let Some((u, v, w)) = cheapest[node] {
-                let set1 = find(&mut parent, u);
-                let set2 = find(&mut parent, v);
-                if set1 != set2 {
-                    mst_weight += w;
-                    union_set(&mut parent, &mut rank, set1, set2);
-                    result[u].push(v);
-                    result[v].push(u); // For undirected graph
-                    num_trees -= 1;
-                }
+    #[test]
+    fn corridor_lines_keep_their_offset_around_bends() {
+        let centerline = vec![(0.0, 0.0), (200.0, 0.0), (200.0, 150.0), (350.0, 300.0)];
+        let lines = discretize_corridor(centerline.clone(), 60.0, 25.0, 20.0).unwrap();
+
+        let offsets: [f64; 3] = [-25.0, 0.0, 25.0];
+        assert_eq!(lines.len(), offsets.len());
+        for (line, offset) in lines.iter().zip(offsets) {
+            assert!(!line.is_empty());
+            for point in line {
+                // Centers past the end of a line lie beyond the centerline, as in a column
+                let distance = polyline_distance(point, &centerline);
+                assert!(distance <= offset.abs() + 20.0 + 1e-6, "{:?}", point);
+            }
+            for pair in line.windows(2) {
+                assert!(euclidean_distance(&pair[0], &pair[1]) <= 20.0 + 1e-9);
             }
         }
+    }
 
-        cheapest.iter_mut().for_each(|x| *x = None);
+    #[test]
+    fn corridor_drops_repeated_vertices() {
+        let clean = discretize_corridor(vec![(0.0, 0.0), (80.0, 60.0)], 10.0, 10.0, 10.0);
+        let repeated = discretize_corridor(
+            vec![(0.0, 0.0), (0.0, 0.0), (80.0, 60.0), (80.0, 60.0)],
+            10.0,
+            10.0,
+            10.0,
+        );
+        assert_eq!(clean.unwrap(), repeated.unwrap());
     }
 
-    println!("Weight of MST is {}", mst_weight);
-    result
-}
+    #[test]
+    fn corridor_rejects_degenerate_input() {
+        assert!(discretize_corridor(vec![(5.0, 5.0), (5.0, 5.0)], 10.0, 10.0, 10.0).is_err());
+        assert!(discretize_corridor(vec![(0.0, 0.0), (10.0, 0.0)], 0.0, 10.0, 10.0).is_err());
+        assert!(discretize_corridor(vec![(0.0, 0.0), (10.0, 0.0)], 10.0, -1.0, 10.0).is_err());
+    }
 
-#[tauri::command]
-pub fn search_long_distance(
-    points: Vec<(f64, f64)>,
-    start_point: (f64, f64),
-) -> Result<f64, String> {
-    if points.is_empty() {
-        return Err("No points provided".to_string());
+    // Photo centers of a full grid, origin and spacing are in the frame rotated by the direction
+    fn grid(
+        origin: (f64, f64),
+        size: (usize, usize),
+        spacing: (f64, f64),
+        direction_radians: f64,
+    ) -> Vec<Vec<(f64, f64)>> {
+        (0..size.0)
+            .map(|i| {
+                (0..size.1)
+                    .map(|j| {
+                        let x = origin.0 + (i as f64 + 0.5) * spacing.0;
+                        let y = origin.1 + (j as f64 + 0.5) * spacing.1;
+                        coordinate_restore(x, y, direction_radians)
+                    })
+                    .collect()
+            })
+            .collect()
     }
 
-    let max_distance = points.iter()
-        .map(|&point| euclidean_distance(&start_point, &point))
-        .fold(f64::NEG_INFINITY, |max_dist, dist| f64::max(max_dist, dist));
+    fn point_count(region: &[Vec<(f64, f64)>]) -> usize {
+        region.iter().map(|column| column.len()).sum()
+    }
 
-    Ok(max_distance) 
+    #[test]
+    fn touching_regions_keep_every_point() {
+        for direction_radians in [0.0, 0.5] {
+            let a = grid((0.0, 0.0), (10, 10), (10.0, 10.0), direction_radians);
+            let b = grid((100.0, 0.0), (10, 10), (10.0, 10.0), direction_radians);
+            let c = grid((0.0, 100.0), (10, 10), (10.0, 10.0), direction_radians);
+            let clipped = clip_overlapping_regions(vec![
+                (a.clone(), direction_radians),
+                (b.clone(), direction_radians),
+                (c.clone(), direction_radians),
+            ]);
+            assert_eq!(clipped.len(), 3);
+            assert_eq!(clipped[0].0, a);
+            assert_eq!(clipped[1].0, b);
+            assert_eq!(clipped[2].0, c);
+        }
+    }
+
+    #[test]
+    fn overlapping_regions_drop_the_photographed_points() {
+        let a = grid((0.0, 0.0), (10, 10), (10.0, 10.0), 0.0);
+        let b = grid((50.0, 0.0), (10, 10), (10.0, 10.0), 0.0);
+        let clipped = clip_overlapping_regions(vec![(a.clone(), 0.0), (b, 0.0)]);
+        assert_eq!(clipped[0].0, a);
+        assert_eq!(clipped[1].0, grid((100.0, 0.0), (5, 10), (10.0, 10.0), 0.0));
+
+        // A rotated region over the square keeps exactly its points outside the square
+        let rotated = grid((60.0, -40.0), (8, 8), (10.0, 10.0), PI / 4.0);
+        let clipped = clip_overlapping_regions(vec![(a, 0.0), (rotated.clone(), PI / 4.0)]);
+        let kept: Vec<(f64, f64)> = clipped[1].0.iter().flatten().copied().collect();
+        for point in rotated.iter().flatten() {
+            let inside = (0.0..=100.0).contains(&point.0) && (0.0..=100.0).contains(&point.1);
+            assert_eq!(kept.contains(point), !inside, "{:?}", point);
+        }
+        assert!(!kept.is_empty() && kept.len() < point_count(&rotated));
+    }
+
+    #[test]
+    fn covered_region_disappears() {
+        let a = grid((0.0, 0.0), (10, 10), (10.0, 10.0), 0.0);
+        let inner = grid((20.0, 20.0), (4, 4), (10.0, 10.0), 0.0);
+        let clipped = clip_overlapping_regions(vec![(a, 0.0), (inner, 0.0)]);
+        assert_eq!(clipped.len(), 1);
+    }
+
+    #[test]
+    fn clipping_follows_the_photos_of_an_uneven_region() {
+        // Staircase below the diagonal, its bounding rectangle would cover the whole square
+        let staircase: Vec<Vec<(f64, f64)>> = grid((0.0, 0.0), (10, 10), (10.0, 10.0), 0.0)
+            .into_iter()
+            .enumerate()
+            .map(|(i, column)| column.into_iter().take(i + 1).collect())
+            .collect();
+        let corner = grid((0.0, 50.0), (5, 5), (10.0, 10.0), 0.0);
+        let clipped = clip_overlapping_regions(vec![(staircase, 0.0), (corner.clone(), 0.0)]);
+        assert_eq!(clipped[1].0, corner);
+    }
+
+    #[test]
+    fn single_row_region_covers_square_photos() {
+        let row = grid((0.0, 0.0), (10, 1), (10.0, 10.0), 0.0);
+        let below = grid((0.0, 0.0), (10, 3), (10.0, 10.0), 0.0);
+        let clipped = clip_overlapping_regions(vec![(row, 0.0), (below, 0.0)]);
+        assert_eq!(clipped[1].0, grid((0.0, 10.0), (10, 2), (10.0, 10.0), 0.0));
+    }
+
+    #[test]
+    fn single_column_region_covers_square_photos() {
+        let column = grid((0.0, 0.0), (1, 10), (10.0, 10.0), 0.0);
+        let beside = grid((0.0, 0.0), (3, 10), (10.0, 10.0), 0.0);
+        let clipped = clip_overlapping_regions(vec![(column, 0.0), (beside, 0.0)]);
+        assert_eq!(clipped[1].0, grid((10.0, 0.0), (2, 10), (10.0, 10.0), 0.0));
+    }
+
+    #[test]
+    fn region_ordering_stops_when_cancelled() {
+        let sweeps: Vec<Vec<Sweep>> = (0..4)
+            .map(|i| region_sweeps(&grid((i as f64 * 100.0, 0.0), (3, 3), (10.0, 10.0), 0.0)))
+            .collect();
+        let cancelled = JobContext::stoppable(Arc::new(AtomicBool::new(true)));
+        assert!(matches!(
+            exact_region_order(&sweeps, (0.0, 0.0), &cancelled),
+            Err(Error::Cancelled(_))
+        ));
+        assert!(matches!(
+            improve_region_order(vec![0, 1, 2, 3], &sweeps, (0.0, 0.0), &cancelled),
+            Err(Error::Cancelled(_))
+        ));
+
+        let context = JobContext::detached();
+        let exact = exact_region_order(&sweeps, (0.0, 0.0), &context).unwrap();
+        let improved = improve_region_order(vec![2, 0, 3, 1], &sweeps, (0.0, 0.0), &context);
+        for order in [exact, improved.unwrap()] {
+            let mut regions: Vec<usize> = order.iter().map(|&(region, _)| region).collect();
+            regions.sort_unstable();
+            assert_eq!(regions, vec![0, 1, 2, 3]);
+        }
+    }
+
+    #[test]
+    fn regions_are_discretized_with_their_own_settings() {
+        let square = vec![(0.0, 0.0), (100.0, 0.0), (100.0, 100.0), (0.0, 100.0)];
+        let region = |photo_width, photo_height, direction_degrees| SurveyRegion {
+            polygon: square.clone(),
+            photo_width,
+            photo_height,
+            direction_degrees,
+        };
+        let regions = vec![region(10.0, 20.0, 0.0), region(25.0, 25.0, 30.0)];
+        let discretized = discretize_regions(regions.clone(), true).unwrap();
+        assert_eq!(discretized.len(), 2);
+        for (grid, region) in discretized.iter().zip(regions) {
+            let expected = discretize_area(
+                vec![region.polygon],
+                region.photo_width,
+                region.photo_height,
+                region.direction_degrees,
+                true,
+            )
+            .unwrap();
+            assert_eq!(grid, &expected[0]);
+        }
+        assert_ne!(discretized[0], discretized[1]);
+
+        for (photo_width, photo_height) in [(0.0, 10.0), (10.0, 0.0), (-5.0, 10.0)] {
+            assert!(matches!(
+                discretize_regions(vec![region(photo_width, photo_height, 0.0)], true),
+                Err(Error::Validation(_))
+            ));
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn overlapping_regions_are_photographed_once(
+            // Single rows and columns of the first region have their spacing guessed
+            size in ((2usize..8, 2usize..8), (1usize..8, 1usize..8)),
+            spacing in (10.0..30.0, 10.0..30.0),
+            offset in (-100.0..100.0, -100.0..100.0),
+            directions in (0.0..PI, 0.0..PI),
+            start_point in (-200.0..200.0, -200.0..200.0),
+        ) {
+            let first = grid((0.0, 0.0), size.0, spacing, directions.0);
+            let second = grid(offset, size.1, spacing, directions.1);
+            let route = rectangular_regions(
+                vec![first.clone(), second.clone()],
+                start_point,
+                vec![directions.0 * 180.0 / PI, directions.1 * 180.0 / PI],
+            )
+            .unwrap();
+
+            // The first region is flown whole, the second one where the first took no photos
+            let first_centers: Vec<(f64, f64)> = first.iter().flatten().copied().collect();
+            let photographed = |point: &(f64, f64)| {
+                let (x, y) = coordinate_transformation(point.0, point.1, directions.0);
+                first_centers.iter().any(|center| {
+                    let (cx, cy) = coordinate_transformation(center.0, center.1, directions.0);
+                    (x - cx).abs() <= spacing.0 / 2.0 + 1e-6
+                        && (y - cy).abs() <= spacing.1 / 2.0 + 1e-6
+                })
+            };
+            let expected: Vec<(f64, f64)> = second
+                .iter()
+                .flatten()
+                .filter(|point| !photographed(point))
+                .chain(first_centers.iter())
+                .copied()
+                .collect();
+            assert_visits_each_once(&route, start_point, &expected);
+        }
+    }
 }
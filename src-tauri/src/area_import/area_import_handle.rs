@@ -0,0 +1,13 @@
+use crate::area_import;
+use crate::error::Result;
+use crate::projection::Projection;
+use std::path::Path;
+
+// Survey area polygons of a GeoJSON, GPX, CSV, KML or Shapefile boundary file in the given
+// projection, EPSG:3857 for the map or the planning projection for discretize_area
+#[tauri::command]
+pub fn import_area(path: String, projection: String) -> Result<Vec<Vec<(f64, f64)>>> {
+    println!("Received import area: {}", path);
+    let projection = Projection::from_code(&projection)?;
+    area_import::read_area(Path::new(&path), projection)
+}
@@ -0,0 +1,37 @@
+use crate::area_import::{check_lon_lat, AreaPolygon};
+use crate::csv_table::{CsvTable, LAT_COLUMNS, LON_COLUMNS};
+use crate::error::{Error, Result};
+
+// Polygon vertices from a table with lat and lon columns. Rows with the same value in the
+// polygon (or area) column form one polygon, without that column the file is a single polygon.
+pub fn parse_polygons(contents: &str) -> Result<Vec<AreaPolygon>> {
+    let table = CsvTable::parse(contents, "Area file is empty.")?;
+    let (lat_column, lon_column) = match (table.column(LAT_COLUMNS), table.column(LON_COLUMNS)) {
+        (Some(lat), Some(lon)) => (lat, lon),
+        _ => return Err(Error::validation("Area CSV needs lat and lon columns.")),
+    };
+    let polygon_column = table.column(&["polygon", "area", "polygon_id", "area_id"]);
+
+    // Polygons in the order they first appear
+    let mut polygons: Vec<(String, Vec<(f64, f64)>)> = Vec::new();
+    for row in table.rows() {
+        let invalid =
+            || Error::validation(format!("Invalid area row {}: {}", row.number, row.line));
+
+        let lat: f64 = row.field(lat_column).parse().map_err(|_| invalid())?;
+        let lon: f64 = row.field(lon_column).parse().map_err(|_| invalid())?;
+        let point = check_lon_lat(lon, lat)?;
+
+        let key = polygon_column
+            .map(|column| row.field(column))
+            .unwrap_or_default();
+        match polygons.iter_mut().find(|(name, _)| name == key) {
+            Some((_, polygon)) => polygon.push(point),
+            None => polygons.push((key.to_string(), vec![point])),
+        }
+    }
+    Ok(polygons
+        .into_iter()
+        .map(|(_, polygon)| AreaPolygon::new(polygon))
+        .collect())
+}
@@ -0,0 +1,72 @@
+use crate::area_import::{check_lon_lat, AreaPolygon};
+use crate::error::{Error, Result};
+use serde_json::Value;
+
+// Every Polygon and MultiPolygon with its holes, in longitude and latitude.
+// Points and lines, like the photos and route of an exported mission, are skipped.
+pub fn parse_polygons(contents: &str) -> Result<Vec<AreaPolygon>> {
+    let value: Value = serde_json::from_str(contents)
+        .map_err(|e| Error::validation(format!("Invalid GeoJSON: {}", e)))?;
+    let mut polygons = Vec::new();
+    collect_polygons(&value, &mut polygons)?;
+    Ok(polygons)
+}
+
+fn collect_polygons(value: &Value, polygons: &mut Vec<AreaPolygon>) -> Result<()> {
+    let members = |name: &str| value[name].as_array().cloned().unwrap_or_default();
+
+    match value["type"].as_str() {
+        Some("FeatureCollection") => {
+            for feature in members("features") {
+                collect_polygons(&feature, polygons)?;
+            }
+        }
+        Some("Feature") => collect_polygons(&value["geometry"], polygons)?,
+        Some("GeometryCollection") => {
+            for geometry in members("geometries") {
+                collect_polygons(&geometry, polygons)?;
+            }
+        }
+        Some("Polygon") => polygons.push(polygon(&value["coordinates"])?),
+        Some("MultiPolygon") => {
+            for coordinates in members("coordinates") {
+                polygons.push(polygon(&coordinates)?);
+            }
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+// The first ring is the outline, the others are holes
+fn polygon(value: &Value) -> Result<AreaPolygon> {
+    let rings = value
+        .as_array()
+        .ok_or_else(|| Error::validation("GeoJSON polygon without coordinates."))?;
+    let mut rings = rings.iter().map(ring);
+    let exterior = rings
+        .next()
+        .ok_or_else(|| Error::validation("GeoJSON polygon without coordinates."))??;
+    Ok(AreaPolygon {
+        exterior,
+        holes: rings.collect::<Result<_>>()?,
+    })
+}
+
+fn ring(value: &Value) -> Result<Vec<(f64, f64)>> {
+    let positions = value
+        .as_array()
+        .ok_or_else(|| Error::validation("GeoJSON polygon without coordinates."))?;
+    positions
+        .iter()
+        .map(
+            |position| match (position[0].as_f64(), position[1].as_f64()) {
+                (Some(lon), Some(lat)) => check_lon_lat(lon, lat),
+                _ => Err(Error::validation(format!(
+                    "Invalid GeoJSON position: {}",
+                    position
+                ))),
+            },
+        )
+        .collect()
+}
@@ -0,0 +1,41 @@
+use crate::area_import::{check_lon_lat, AreaPolygon};
+use crate::error::{Error, Result};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+// Every route and track segment as a polygon outline, in longitude and latitude
+pub fn parse_polygons(contents: &str) -> Result<Vec<AreaPolygon>> {
+    let invalid = |e: quick_xml::Error| Error::validation(format!("Invalid GPX: {}", e));
+
+    let mut reader = Reader::from_str(contents);
+    reader.trim_text(true);
+
+    let mut polygons = Vec::new();
+    let mut ring = Vec::new();
+    loop {
+        match reader.read_event().map_err(invalid)? {
+            Event::Start(e) | Event::Empty(e)
+                if matches!(e.local_name().as_ref(), b"rtept" | b"trkpt") =>
+            {
+                ring.push(point(&e)?);
+            }
+            Event::End(e) if matches!(e.local_name().as_ref(), b"rte" | b"trkseg") => {
+                polygons.push(AreaPolygon::new(std::mem::take(&mut ring)));
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+    Ok(polygons)
+}
+
+fn point(element: &BytesStart) -> Result<(f64, f64)> {
+    let coordinate = |name: &str| -> Result<f64> {
+        element
+            .try_get_attribute(name)
+            .map_err(|e| Error::validation(format!("Invalid GPX: {}", e)))?
+            .and_then(|attribute| attribute.unescape_value().ok()?.parse().ok())
+            .ok_or_else(|| Error::validation(format!("GPX point without {}", name)))
+    };
+    check_lon_lat(coordinate("lon")?, coordinate("lat")?)
+}
@@ -0,0 +1,91 @@
+use crate::area_import::{check_lon_lat, AreaPolygon};
+use crate::error::{Error, Result};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+// Every Polygon with its inner boundaries as holes, KML is always in longitude and latitude
+pub fn parse_polygons(contents: &str) -> Result<Vec<AreaPolygon>> {
+    let invalid = |e: quick_xml::Error| Error::validation(format!("Invalid KML: {}", e));
+
+    let mut reader = Reader::from_str(contents);
+    reader.trim_text(true);
+
+    let mut polygons = Vec::new();
+    let mut polygon: Option<AreaPolygon> = None;
+    let mut inner_boundary = false;
+    let mut coordinates: Option<String> = None;
+
+    loop {
+        match reader.read_event().map_err(invalid)? {
+            Event::Start(e) => match e.local_name().as_ref() {
+                b"Polygon" => polygon = Some(AreaPolygon::new(Vec::new())),
+                b"outerBoundaryIs" => inner_boundary = false,
+                b"innerBoundaryIs" => inner_boundary = true,
+                b"coordinates" if polygon.is_some() => coordinates = Some(String::new()),
+                _ => (),
+            },
+            Event::Text(text) => {
+                if let Some(coordinates) = coordinates.as_mut() {
+                    coordinates.push_str(&text.unescape().map_err(invalid)?);
+                    coordinates.push(' ');
+                }
+            }
+            Event::End(e) => match e.local_name().as_ref() {
+                b"coordinates" => {
+                    if let (Some(text), Some(polygon)) = (coordinates.take(), polygon.as_mut()) {
+                        let ring = ring(&text)?;
+                        if inner_boundary {
+                            polygon.holes.push(ring);
+                        } else {
+                            polygon.exterior = ring;
+                        }
+                    }
+                }
+                b"Polygon" => polygons.extend(polygon.take()),
+                _ => (),
+            },
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+    Ok(polygons)
+}
+
+// Whitespace separated "lon,lat[,alt]" tuples
+fn ring(text: &str) -> Result<Vec<(f64, f64)>> {
+    text.split_whitespace()
+        .map(|tuple| {
+            let mut values = tuple.split(',').map(|value| value.parse::<f64>());
+            match (values.next(), values.next()) {
+                (Some(Ok(lon)), Some(Ok(lat))) => check_lon_lat(lon, lat),
+                _ => Err(Error::validation(format!(
+                    "Invalid KML coordinates: {}",
+                    tuple
+                ))),
+            }
+        })
+        .collect()
+}
+
+// The main document of a KMZ, doc.kml or else the first KML in the archive
+pub fn read_kmz(path: &Path) -> Result<String> {
+    let zip_error = |e: zip::result::ZipError| Error::validation(format!("Invalid KMZ: {}", e));
+    let mut archive = zip::ZipArchive::new(File::open(path)?).map_err(zip_error)?;
+
+    let name = archive
+        .file_names()
+        .filter(|name| name.to_lowercase().ends_with(".kml"))
+        .min_by_key(|name| !name.eq_ignore_ascii_case("doc.kml"))
+        .map(str::to_string)
+        .ok_or_else(|| Error::validation("KMZ does not contain a KML document."))?;
+
+    let mut contents = String::new();
+    archive
+        .by_name(&name)
+        .map_err(zip_error)?
+        .read_to_string(&mut contents)?;
+    Ok(contents)
+}
@@ -0,0 +1,192 @@
+use crate::algorithms::euclidean_distance;
+use crate::error::{Error, Result};
+use crate::projection::Projection;
+use std::path::Path;
+pub mod area_import_handle;
+pub mod csv;
+pub mod geojson;
+pub mod gpx;
+pub mod kml;
+pub mod shapefile;
+
+// File formats survey areas can be imported from, picked by the file extension
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AreaFormat {
+    GeoJson,   // Polygon and MultiPolygon geometries
+    Gpx,       // every route and track segment is a polygon outline
+    Csv,       // lat and lon columns, rows grouped by an optional polygon column
+    Kml,       // Polygon placemarks, also inside MultiGeometry
+    Kmz,       // zipped KML
+    Shapefile, // .shp with its .prj next to it, or both zipped together
+}
+
+impl AreaFormat {
+    pub fn from_path(path: &Path) -> Result<AreaFormat> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+
+        match extension.as_str() {
+            "geojson" | "json" => Ok(AreaFormat::GeoJson),
+            "gpx" => Ok(AreaFormat::Gpx),
+            "csv" => Ok(AreaFormat::Csv),
+            "kml" => Ok(AreaFormat::Kml),
+            "kmz" => Ok(AreaFormat::Kmz),
+            "shp" | "zip" => Ok(AreaFormat::Shapefile),
+            _ => Err(Error::validation(format!(
+                "Unsupported area format: {}",
+                path.display()
+            ))),
+        }
+    }
+}
+
+// Area polygon with the holes cut out of it, as read from a file
+#[derive(Debug, Clone, PartialEq)]
+pub struct AreaPolygon {
+    pub exterior: Vec<(f64, f64)>,
+    pub holes: Vec<Vec<(f64, f64)>>,
+}
+
+impl AreaPolygon {
+    pub fn new(exterior: Vec<(f64, f64)>) -> AreaPolygon {
+        AreaPolygon {
+            exterior,
+            holes: Vec::new(),
+        }
+    }
+
+    // Longitude and latitude to the projection, degenerate rings are dropped
+    fn project(self, projection: Projection) -> AreaPolygon {
+        let project = |ring: Vec<(f64, f64)>| -> Vec<(f64, f64)> {
+            open_ring(ring)
+                .into_iter()
+                .map(|(lon, lat)| projection.from_lon_lat(lon, lat))
+                .collect()
+        };
+        AreaPolygon {
+            exterior: project(self.exterior),
+            holes: self
+                .holes
+                .into_iter()
+                .map(project)
+                .filter(|hole| hole.len() >= 3)
+                .collect(),
+        }
+    }
+
+    // Single ring with every hole joined to the outline by a bridge walked there and back.
+    // The bridge edges cancel out in the even-odd test of discretize_area, so points inside
+    // a hole are outside the ring.
+    pub fn into_ring(self) -> Vec<(f64, f64)> {
+        let mut ring = self.exterior;
+        let mut holes = self.holes;
+        // Rightmost holes first, their bridges can not cross the holes still to come
+        holes.sort_by(|a, b| max_x(b).total_cmp(&max_x(a)));
+
+        for (i, hole) in holes.iter().enumerate() {
+            let (hole_index, hole_point) = hole
+                .iter()
+                .copied()
+                .enumerate()
+                .max_by(|a, b| a.1 .0.total_cmp(&b.1 .0))
+                .unwrap_or((0, hole[0]));
+
+            // Closest outline vertex the bridge can reach without crossing any edge
+            let mut candidates: Vec<usize> = (0..ring.len()).collect();
+            candidates.sort_by(|&a, &b| {
+                euclidean_distance(&ring[a], &hole_point)
+                    .total_cmp(&euclidean_distance(&ring[b], &hole_point))
+            });
+            let blocked = |ring_index: usize| {
+                let bridge = (ring[ring_index], hole_point);
+                edges(&ring)
+                    .chain(holes[i..].iter().flat_map(|hole| edges(hole)))
+                    .any(|edge| crosses(bridge, edge))
+            };
+            let ring_index = candidates
+                .iter()
+                .copied()
+                .find(|&candidate| !blocked(candidate))
+                .unwrap_or(candidates[0]);
+
+            let mut bridged = ring[..=ring_index].to_vec();
+            bridged.extend(hole[hole_index..].iter().chain(&hole[..=hole_index]));
+            bridged.extend(&ring[ring_index..]);
+            ring = bridged;
+        }
+        ring
+    }
+}
+
+fn max_x(ring: &[(f64, f64)]) -> f64 {
+    ring.iter()
+        .map(|point| point.0)
+        .fold(f64::NEG_INFINITY, f64::max)
+}
+
+fn edges(ring: &[(f64, f64)]) -> impl Iterator<Item = ((f64, f64), (f64, f64))> + '_ {
+    ring.iter()
+        .zip(ring.iter().cycle().skip(1))
+        .map(|(a, b)| (*a, *b))
+}
+
+// Proper crossing of two segments, touching at an end point does not count
+fn crosses(a: ((f64, f64), (f64, f64)), b: ((f64, f64), (f64, f64))) -> bool {
+    let side = |p: (f64, f64), q: (f64, f64), r: (f64, f64)| {
+        ((q.0 - p.0) * (r.1 - p.1) - (q.1 - p.1) * (r.0 - p.0)).signum()
+    };
+    let shares_point = a.0 == b.0 || a.0 == b.1 || a.1 == b.0 || a.1 == b.1;
+    !shares_point
+        && side(a.0, a.1, b.0) * side(a.0, a.1, b.1) < 0.0
+        && side(b.0, b.1, a.0) * side(b.0, b.1, a.1) < 0.0
+}
+
+// Read the survey area polygons of a file and project them, the result can go straight
+// into discretize_area. Rings are returned open, without the closing point, and holes
+// are bridged into the outline of their polygon.
+pub fn read_area(path: &Path, projection: Projection) -> Result<Vec<Vec<(f64, f64)>>> {
+    let polygons = match AreaFormat::from_path(path)? {
+        AreaFormat::GeoJson => geojson::parse_polygons(&std::fs::read_to_string(path)?)?,
+        AreaFormat::Gpx => gpx::parse_polygons(&std::fs::read_to_string(path)?)?,
+        AreaFormat::Csv => csv::parse_polygons(&std::fs::read_to_string(path)?)?,
+        AreaFormat::Kml => kml::parse_polygons(&std::fs::read_to_string(path)?)?,
+        AreaFormat::Kmz => kml::parse_polygons(&kml::read_kmz(path)?)?,
+        AreaFormat::Shapefile => shapefile::read_polygons(path)?,
+    };
+
+    let rings: Vec<Vec<(f64, f64)>> = polygons
+        .into_iter()
+        .map(|polygon| polygon.project(projection))
+        .filter(|polygon| polygon.exterior.len() >= 3)
+        .map(AreaPolygon::into_ring)
+        .collect();
+
+    if rings.is_empty() {
+        return Err(Error::validation(format!(
+            "No polygons found in {}",
+            path.display()
+        )));
+    }
+    Ok(rings)
+}
+
+fn open_ring(mut ring: Vec<(f64, f64)>) -> Vec<(f64, f64)> {
+    if ring.len() > 1 && ring.first() == ring.last() {
+        ring.pop();
+    }
+    ring
+}
+
+pub(crate) fn check_lon_lat(lon: f64, lat: f64) -> Result<(f64, f64)> {
+    if (-180.0..=180.0).contains(&lon) && (-90.0..=90.0).contains(&lat) {
+        Ok((lon, lat))
+    } else {
+        Err(Error::validation(format!(
+            "Coordinates out of range: {}, {}",
+            lon, lat
+        )))
+    }
+}
@@ -0,0 +1,220 @@
+use crate::area_import::{check_lon_lat, AreaPolygon};
+use crate::error::{Error, Result};
+use crate::projection::Projection;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+const FILE_CODE: i32 = 9994;
+const HEADER_LENGTH: usize = 100;
+
+// Shape types with polygon rings, plain and with Z or M values
+const NULL_SHAPE: i32 = 0;
+const POLYGON: i32 = 5;
+const POLYGON_Z: i32 = 15;
+const POLYGON_M: i32 = 25;
+
+// Polygons of a .shp file, reprojected to longitude and latitude with the .prj next to it.
+// A .zip holding the .shp and .prj is read the same way. Without a .prj the coordinates
+// must already be longitude and latitude.
+pub fn read_polygons(path: &Path) -> Result<Vec<AreaPolygon>> {
+    let is_zip = path
+        .extension()
+        .map_or(false, |extension| extension.eq_ignore_ascii_case("zip"));
+    let (shp, prj) = if is_zip {
+        read_zip(path)?
+    } else {
+        (
+            std::fs::read(path)?,
+            std::fs::read_to_string(path.with_extension("prj")).ok(),
+        )
+    };
+
+    let source = match prj {
+        Some(wkt) => Projection::from_wkt(&wkt)?,
+        None => None,
+    };
+
+    let mut polygons = Vec::new();
+    for rings in parse_records(&shp)? {
+        for polygon in assign_holes(rings) {
+            let to_lon_lat = |ring: Vec<(f64, f64)>| -> Result<Vec<(f64, f64)>> {
+                ring.into_iter()
+                    .map(|(x, y)| match source {
+                        Some(projection) => {
+                            let (lon, lat) = projection.to_lon_lat(x, y);
+                            check_lon_lat(lon, lat)
+                        }
+                        None => check_lon_lat(x, y),
+                    })
+                    .collect()
+            };
+            polygons.push(AreaPolygon {
+                exterior: to_lon_lat(polygon.exterior)?,
+                holes: polygon
+                    .holes
+                    .into_iter()
+                    .map(to_lon_lat)
+                    .collect::<Result<_>>()?,
+            });
+        }
+    }
+    Ok(polygons)
+}
+
+fn read_zip(path: &Path) -> Result<(Vec<u8>, Option<String>)> {
+    let zip_error = |e: zip::result::ZipError| Error::validation(format!("Invalid zip: {}", e));
+    let mut archive = zip::ZipArchive::new(File::open(path)?).map_err(zip_error)?;
+    let find = |extension: &str| {
+        archive
+            .file_names()
+            .find(|name| name.to_lowercase().ends_with(extension))
+            .map(str::to_string)
+    };
+    let (shp_name, prj_name) = (find(".shp"), find(".prj"));
+
+    let shp_name =
+        shp_name.ok_or_else(|| Error::validation("Zip does not contain a .shp file."))?;
+    let mut shp = Vec::new();
+    archive
+        .by_name(&shp_name)
+        .map_err(zip_error)?
+        .read_to_end(&mut shp)?;
+
+    let prj = match prj_name {
+        Some(name) => {
+            let mut prj = String::new();
+            archive
+                .by_name(&name)
+                .map_err(zip_error)?
+                .read_to_string(&mut prj)?;
+            Some(prj)
+        }
+        None => None,
+    };
+    Ok((shp, prj))
+}
+
+// Rings of every polygon record, in the coordinates of the file
+fn parse_records(shp: &[u8]) -> Result<Vec<Vec<Vec<(f64, f64)>>>> {
+    let invalid = || Error::validation("Invalid shapefile.");
+    let bytes = |offset: usize| -> Result<[u8; 4]> {
+        offset
+            .checked_add(4)
+            .and_then(|end| shp.get(offset..end))
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(invalid)
+    };
+    let big_endian = |offset: usize| bytes(offset).map(i32::from_be_bytes);
+    let little_endian = |offset: usize| bytes(offset).map(i32::from_le_bytes);
+    let double = |offset: usize| -> Result<f64> {
+        offset
+            .checked_add(8)
+            .and_then(|end| shp.get(offset..end))
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(f64::from_le_bytes)
+            .ok_or_else(invalid)
+    };
+    // Counts and offsets are stored signed, negative ones only come from broken files
+    let to_usize = |value: i32| usize::try_from(value).map_err(|_| invalid());
+    // Offset of the index-th item of the given size after start
+    let item = |start: usize, size: usize, index: usize| {
+        size.checked_mul(index)
+            .and_then(|item_offset| start.checked_add(item_offset))
+            .ok_or_else(invalid)
+    };
+
+    if big_endian(0)? != FILE_CODE || shp.len() < HEADER_LENGTH {
+        return Err(invalid());
+    }
+
+    let mut records = Vec::new();
+    let mut offset = HEADER_LENGTH;
+    while offset.checked_add(8).map_or(false, |end| end <= shp.len()) {
+        // Lengths are counted in 16 bit words
+        let content_length = to_usize(big_endian(offset + 4)?)?
+            .checked_mul(2)
+            .ok_or_else(invalid)?;
+        let content = offset + 8;
+        offset = content.checked_add(content_length).ok_or_else(invalid)?;
+
+        match little_endian(content)? {
+            NULL_SHAPE => continue,
+            POLYGON | POLYGON_Z | POLYGON_M => (),
+            shape_type => {
+                return Err(Error::validation(format!(
+                    "Shapefile does not contain polygons, shape type {}",
+                    shape_type
+                )))
+            }
+        }
+
+        // Shape type and bounding box come before the part and point counts
+        let part_count = to_usize(little_endian(content + 36)?)?;
+        let point_count = to_usize(little_endian(content + 40)?)?;
+        let parts = content + 44;
+        let points = item(parts, 4, part_count)?;
+
+        let mut starts = (0..part_count)
+            .map(|i| to_usize(little_endian(item(parts, 4, i)?)?))
+            .collect::<Result<Vec<usize>>>()?;
+        starts.push(point_count);
+
+        let mut rings = Vec::new();
+        for part in starts.windows(2) {
+            if part[0] > part[1] {
+                return Err(invalid());
+            }
+            let ring = (part[0]..part[1])
+                .map(|i| {
+                    let point = item(points, 16, i)?;
+                    Ok((double(point)?, double(point + 8)?))
+                })
+                .collect::<Result<Vec<(f64, f64)>>>()?;
+            rings.push(ring);
+        }
+        records.push(rings);
+    }
+    Ok(records)
+}
+
+// Shapefile outlines run clockwise and holes counterclockwise, every hole belongs to the
+// outline around it
+fn assign_holes(rings: Vec<Vec<(f64, f64)>>) -> Vec<AreaPolygon> {
+    let (exteriors, holes): (Vec<_>, Vec<_>) =
+        rings.into_iter().partition(|ring| signed_area(ring) <= 0.0);
+    let mut polygons: Vec<AreaPolygon> = exteriors.into_iter().map(AreaPolygon::new).collect();
+
+    for hole in holes {
+        match polygons
+            .iter_mut()
+            .find(|polygon| contains(&polygon.exterior, hole[0]))
+        {
+            Some(polygon) => polygon.holes.push(hole),
+            // Counterclockwise ring outside every outline, some writers ignore the rule
+            None => polygons.push(AreaPolygon::new(hole)),
+        }
+    }
+    polygons
+}
+
+// Shoelace formula, positive for counterclockwise rings
+fn signed_area(ring: &[(f64, f64)]) -> f64 {
+    ring.iter()
+        .zip(ring.iter().cycle().skip(1))
+        .map(|(a, b)| a.0 * b.1 - b.0 * a.1)
+        .sum::<f64>()
+        / 2.0
+}
+
+fn contains(ring: &[(f64, f64)], point: (f64, f64)) -> bool {
+    let mut inside = false;
+    for (a, b) in ring.iter().zip(ring.iter().cycle().skip(1)) {
+        if (a.1 > point.1) != (b.1 > point.1)
+            && point.0 < (b.0 - a.0) * (point.1 - a.1) / (b.1 - a.1) + a.0
+        {
+            inside = !inside;
+        }
+    }
+    inside
+}
@@ -1,42 +1,62 @@
 use crate::camera::camera_sql;
-use crate::camera::Camera;
-use rusqlite::Connection;
+use crate::camera::{Camera, SpeedLimit, DEFAULT_MAX_BLUR};
+use crate::database::Database;
+use crate::error::{Error, Result};
+use tauri::State;
 
 #[tauri::command]
-pub fn new_camera(camera: Camera) -> String {
-    let conn = Connection::open("mydatabase.db").expect("Cant open base");
+pub fn new_camera(camera: Camera, database: State<'_, Database>) -> Result<()> {
+    let conn = database.connection()?;
     println!("Received new camera: {:?}", camera);
-    match camera_sql::insert(&camera, &conn) {
-        Ok(_) => "Ok".to_string(),
-        Err(e) => e.to_string(),
-    }
+    camera_sql::insert(&camera, &conn)?;
+    Ok(())
 }
 
 #[tauri::command]
-pub fn update_camera(camera: Camera) -> String {
-    let conn = Connection::open("mydatabase.db").expect("Cant open base");
+pub fn update_camera(camera: Camera, database: State<'_, Database>) -> Result<()> {
+    let conn = database.connection()?;
     println!("Received updated camera: {:?}", camera);
-    match camera_sql::update(&camera, &conn) {
-        Ok(_) => "Ok".to_string(),
-        Err(e) => e.to_string(),
-    }
+    camera_sql::update(&camera, &conn)?;
+    Ok(())
 }
 
 #[tauri::command]
-pub fn delete_camera(camera: Camera) -> String {
-    let conn = Connection::open("mydatabase.db").expect("Cant open base");
+pub fn delete_camera(camera: Camera, database: State<'_, Database>) -> Result<()> {
+    let conn = database.connection()?;
     println!("Received delete camera: {:?}", camera);
-    match camera_sql::delete(&camera, &conn) {
-        Ok(_) => "Ok".to_string(),
-        Err(e) => e.to_string(),
-    }
+    camera_sql::delete(&camera, &conn)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_all_cameras_vec(database: State<'_, Database>) -> Result<Vec<Camera>> {
+    let conn = database.connection()?;
+    Ok(camera_sql::get_cameras_vec(&conn)?)
 }
 
+// Ground speed the camera allows at the altitude, max_blur is the share of the GSD the image
+// may smear during the exposure
 #[tauri::command]
-pub fn get_all_cameras_vec() -> Vec<Camera> {
-    let conn = Connection::open("mydatabase.db").expect("Cant open base");
-    match camera_sql::get_cameras_vec(&conn) {
-        Ok(result) => result,
-        Err(_) => vec![],
+pub fn capture_speed_limit(
+    camera: Camera,
+    flight_speed: f64,
+    altitude: f64,
+    trigger_distance: f64,
+    max_blur: Option<f64>,
+) -> Result<SpeedLimit> {
+    let max_blur = max_blur.unwrap_or(DEFAULT_MAX_BLUR);
+    if altitude <= 0.0 || trigger_distance <= 0.0 || max_blur <= 0.0 || flight_speed <= 0.0 {
+        return Err(Error::validation(
+            "Altitude, trigger distance, blur and flight speed should be greater than zero",
+        ));
+    }
+
+    let limit = camera.speed_limit(flight_speed, altitude, trigger_distance, max_blur);
+    if limit.reduced {
+        println!(
+            "Flight speed reduced from {:.2} to {:.2} m/s for camera {}",
+            limit.flight_speed, limit.ground_speed, camera.name
+        );
     }
+    Ok(limit)
 }
@@ -1,5 +1,5 @@
 use crate::camera::Camera;
-use rusqlite::{Connection, Result};
+use rusqlite::{Connection, OptionalExtension, Result, Row};
 
 pub fn create_table(conn: &Connection) -> Result<usize> {
     conn.execute(
@@ -18,6 +18,15 @@ pub fn create_table(conn: &Connection) -> Result<usize> {
     )
 }
 
+pub fn add_timing_columns(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE camera ADD COLUMN camera_min_shutter_interval REAL DEFAULT 0 NOT NULL
+            CHECK (camera_min_shutter_interval >= 0);
+        ALTER TABLE camera ADD COLUMN camera_exposure_time REAL DEFAULT 0 NOT NULL
+            CHECK (camera_exposure_time >= 0);",
+    )
+}
+
 pub fn insert(camera: &Camera, conn: &Connection) -> Result<usize> {
     conn.execute(
         "INSERT INTO camera (
@@ -25,14 +34,18 @@ pub fn insert(camera: &Camera, conn: &Connection) -> Result<usize> {
                 camera_mass,
                 camera_fov_x,
                 camera_resolution_x,
-                camera_resolution_y
-            ) VALUES (?1, ?2, ?3, ?4, ?5)",
+                camera_resolution_y,
+                camera_min_shutter_interval,
+                camera_exposure_time
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
         (
             &camera.name,
             &camera.mass,
             &camera.fov_x,
             &camera.resolution_x,
             &camera.resolution_y,
+            &camera.min_shutter_interval,
+            &camera.exposure_time,
         ),
     )
 }
@@ -45,14 +58,18 @@ pub fn update(camera: &Camera, conn: &Connection) -> Result<usize> {
                     camera_mass = ?2,
                     camera_fov_x = ?3,
                     camera_resolution_x = ?4,
-                    camera_resolution_y = ?5
-                WHERE camera_id = ?6",
+                    camera_resolution_y = ?5,
+                    camera_min_shutter_interval = ?6,
+                    camera_exposure_time = ?7
+                WHERE camera_id = ?8",
         (
             &camera.name,
             &camera.mass,
             &camera.fov_x,
             &camera.resolution_x,
             &camera.resolution_y,
+            &camera.min_shutter_interval,
+            &camera.exposure_time,
             &camera.id,
         ),
     )
@@ -66,28 +83,43 @@ pub fn delete(camera: &Camera, conn: &Connection) -> Result<usize> {
     )
 }
 
-pub fn get_cameras(conn: &Connection) -> Result<Vec<Result<Camera>>> {
-    let mut stmt = conn.prepare(
-        "SELECT
+const SELECT_CAMERA: &str = "SELECT
                 camera_id,
                 camera_name,
                 camera_mass,
                 camera_fov_x,
                 camera_resolution_x,
-                camera_resolution_y
-            FROM camera",
-    )?;
+                camera_resolution_y,
+                camera_min_shutter_interval,
+                camera_exposure_time
+            FROM camera";
+
+fn camera_from_row(row: &Row) -> Result<Camera> {
+    Ok(Camera {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        mass: row.get(2)?,
+        fov_x: row.get(3)?,
+        resolution_x: row.get(4)?,
+        resolution_y: row.get(5)?,
+        min_shutter_interval: row.get(6)?,
+        exposure_time: row.get(7)?,
+    })
+}
+
+pub fn get_camera(camera_id: u64, conn: &Connection) -> Result<Option<Camera>> {
+    conn.query_row(
+        &format!("{} WHERE camera_id = ?1", SELECT_CAMERA),
+        (&camera_id,),
+        camera_from_row,
+    )
+    .optional()
+}
+
+pub fn get_cameras(conn: &Connection) -> Result<Vec<Result<Camera>>> {
+    let mut stmt = conn.prepare(SELECT_CAMERA)?;
 
-    let camera_iter = stmt.query_map([], |row| {
-        Ok(Camera {
-            id: row.get(0)?,
-            name: row.get(1)?,
-            mass: row.get(2)?,
-            fov_x: row.get(3)?,
-            resolution_x: row.get(4)?,
-            resolution_y: row.get(5)?,
-        })
-    })?;
+    let camera_iter = stmt.query_map([], camera_from_row)?;
 
     Ok(camera_iter.collect())
 }
@@ -11,6 +11,23 @@ pub struct Camera {
     pub fov_x: f64,        // x-axis viewing angle in degrees
     pub resolution_x: u16, // camera resolution x
     pub resolution_y: u16, // camera resolution y
+    #[serde(default)]
+    pub min_shutter_interval: f64, // shortest time between two photos in seconds, 0 if unknown
+    #[serde(default)]
+    pub exposure_time: f64, // exposure time in seconds, 0 if unknown
+}
+
+// Share of the ground sample distance the image may smear during the exposure
+pub const DEFAULT_MAX_BLUR: f64 = 0.5;
+
+// Ground speed the camera allows, compared with the speed the uav is set to fly
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SpeedLimit {
+    pub flight_speed: f64,       // configured uav flight speed in meters per second
+    pub blur_speed: Option<f64>, // fastest speed keeping motion blur under the limit
+    pub shutter_speed: Option<f64>, // fastest speed the camera can keep triggering at
+    pub ground_speed: f64,       // speed to fly, the lowest of the above
+    pub reduced: bool,           // the flight speed had to be reduced
 }
 
 impl Camera {
@@ -20,6 +37,8 @@ impl Camera {
         fov_x: f64,
         resolution_x: u16,
         resolution_y: u16,
+        min_shutter_interval: f64,
+        exposure_time: f64,
     ) -> Camera {
         Camera {
             id: 0,
@@ -28,6 +47,8 @@ impl Camera {
             fov_x,
             resolution_x,
             resolution_y,
+            min_shutter_interval,
+            exposure_time,
         }
     }
 
@@ -38,8 +59,60 @@ impl Camera {
         let fov_x = rng.gen_range(30.0..180.0);
         let resolution_x = rng.gen_range(1000..6000);
         let resolution_y = rng.gen_range(800..4000);
+        let min_shutter_interval = rng.gen_range(0.5..2.0);
+        let exposure_time = 1.0 / rng.gen_range(500.0..2000.0);
 
-        Camera::new(name, mass, fov_x, resolution_x, resolution_y)
+        Camera::new(
+            name,
+            mass,
+            fov_x,
+            resolution_x,
+            resolution_y,
+            min_shutter_interval,
+            exposure_time,
+        )
+    }
+
+    // Distance between photo centers in meters at the given altitude, the footprint is
+    // reduced by the overlap in percent
+    pub fn photo_size(&self, altitude: f64, overlap: f64) -> (f64, f64) {
+        let footprint_width = (self.fov_x / 2.0).to_radians().tan() * 2.0 * altitude;
+        let photo_width = footprint_width * (1.0 - overlap / 100.0);
+        let photo_height = photo_width * self.resolution_y as f64 / self.resolution_x as f64;
+        (photo_width, photo_height)
+    }
+
+    // Ground sample distance, meters of ground per pixel at the given altitude
+    pub fn gsd(&self, altitude: f64) -> f64 {
+        self.photo_size(altitude, 0.0).0 / self.resolution_x as f64
+    }
+
+    // Cap the flight speed so motion blur stays under max_blur of the GSD and triggers
+    // trigger_distance apart never come faster than the camera can shoot
+    pub fn speed_limit(
+        &self,
+        flight_speed: f64,
+        altitude: f64,
+        trigger_distance: f64,
+        max_blur: f64,
+    ) -> SpeedLimit {
+        let blur_speed =
+            (self.exposure_time > 0.0).then(|| max_blur * self.gsd(altitude) / self.exposure_time);
+        let shutter_speed =
+            (self.min_shutter_interval > 0.0).then(|| trigger_distance / self.min_shutter_interval);
+
+        let ground_speed = [blur_speed, shutter_speed]
+            .into_iter()
+            .flatten()
+            .fold(flight_speed, f64::min);
+
+        SpeedLimit {
+            flight_speed,
+            blur_speed,
+            shutter_speed,
+            ground_speed,
+            reduced: ground_speed < flight_speed,
+        }
     }
 
     pub fn print_camera(&self) {
@@ -0,0 +1,148 @@
+// Survey areas shaped like the fields, plots and corridors planned in practice, with the
+// bounds their plans are expected to stay in. Shared by the algorithm tests and benchmarks.
+pub struct CorpusCase {
+    pub name: &'static str,
+    pub polygons: Vec<Vec<(f64, f64)>>, // survey area polygons in meters
+    pub photo_width: f64,               // spacing between columns in meters
+    pub photo_height: f64,              // spacing along a column in meters
+    pub direction_degrees: f64,         // discretization direction
+    pub start_point: (f64, f64),        // takeoff and landing point
+    pub photo_count: (usize, usize),    // expected range of photo centers, inclusive
+    pub min_coverage: f64,              // share of the area inside some photo footprint
+    pub max_route_length: f64,          // longest acceptable heuristic route in meters
+}
+
+impl CorpusCase {
+    pub fn discretize(&self) -> Vec<Vec<Vec<(f64, f64)>>> {
+        crate::algorithms::discretize_area(
+            self.polygons.clone(),
+            self.photo_width,
+            self.photo_height,
+            self.direction_degrees,
+            true,
+        )
+        .unwrap()
+    }
+
+    pub fn photo_centers(&self) -> Vec<(f64, f64)> {
+        self.discretize().into_iter().flatten().flatten().collect()
+    }
+}
+
+// Small enough for brute force to finish in a test
+pub const BRUTE_FORCE_LIMIT: usize = 10;
+
+pub fn cases() -> Vec<CorpusCase> {
+    vec![
+        CorpusCase {
+            name: "convex field",
+            polygons: vec![vec![
+                (0.0, 0.0),
+                (420.0, -30.0),
+                (510.0, 210.0),
+                (300.0, 380.0),
+                (40.0, 300.0),
+            ]],
+            photo_width: 40.0,
+            photo_height: 30.0,
+            direction_degrees: 15.0,
+            start_point: (-20.0, -20.0),
+            photo_count: (150, 166),
+            min_coverage: 0.99,
+            max_route_length: 5900.0,
+        },
+        CorpusCase {
+            name: "L-shaped field",
+            polygons: vec![vec![
+                (0.0, 0.0),
+                (500.0, 0.0),
+                (500.0, 150.0),
+                (180.0, 150.0),
+                (180.0, 450.0),
+                (0.0, 450.0),
+            ]],
+            photo_width: 35.0,
+            photo_height: 25.0,
+            direction_degrees: 0.0,
+            start_point: (-30.0, 200.0),
+            photo_count: (154, 170),
+            min_coverage: 0.99,
+            max_route_length: 5250.0,
+        },
+        CorpusCase {
+            name: "horseshoe field",
+            polygons: vec![vec![
+                (0.0, 0.0),
+                (400.0, 0.0),
+                (400.0, 300.0),
+                (300.0, 300.0),
+                (300.0, 100.0),
+                (100.0, 100.0),
+                (100.0, 300.0),
+                (0.0, 300.0),
+            ]],
+            photo_width: 30.0,
+            photo_height: 30.0,
+            direction_degrees: 0.0,
+            start_point: (200.0, 200.0),
+            photo_count: (104, 116),
+            min_coverage: 0.99,
+            max_route_length: 4050.0,
+        },
+        CorpusCase {
+            name: "triangular field with sharp tips",
+            polygons: vec![vec![(0.0, 0.0), (600.0, 80.0), (40.0, 160.0)]],
+            photo_width: 30.0,
+            photo_height: 20.0,
+            direction_degrees: 8.0,
+            start_point: (-10.0, 80.0),
+            photo_count: (89, 99),
+            min_coverage: 0.98,
+            max_route_length: 2750.0,
+        },
+        CorpusCase {
+            name: "multi-part plots",
+            polygons: vec![
+                vec![(0.0, 0.0), (150.0, 0.0), (150.0, 100.0), (0.0, 100.0)],
+                vec![(400.0, 50.0), (560.0, 20.0), (600.0, 160.0), (430.0, 190.0)],
+                vec![(100.0, 400.0), (260.0, 380.0), (230.0, 520.0)],
+            ],
+            photo_width: 25.0,
+            photo_height: 20.0,
+            direction_degrees: 0.0,
+            start_point: (250.0, 250.0),
+            photo_count: (121, 135),
+            min_coverage: 0.99,
+            max_route_length: 4200.0,
+        },
+        // A strip no wider than one photo gets no centers at all from discretize_area,
+        // such roads are planned with discretize_corridor instead
+        CorpusCase {
+            name: "road strip",
+            polygons: vec![vec![
+                (0.0, 0.0),
+                (1732.05, 1000.0),
+                (1702.05, 1051.96),
+                (-30.0, 51.96),
+            ]],
+            photo_width: 50.0,
+            photo_height: 40.0,
+            direction_degrees: 30.0,
+            start_point: (0.0, -30.0),
+            photo_count: (76, 84),
+            min_coverage: 0.99,
+            max_route_length: 5900.0,
+        },
+        CorpusCase {
+            name: "small plot",
+            polygons: vec![vec![(0.0, 0.0), (90.0, 0.0), (100.0, 60.0), (10.0, 70.0)]],
+            photo_width: 35.0,
+            photo_height: 30.0,
+            direction_degrees: 0.0,
+            start_point: (-10.0, -10.0),
+            photo_count: (8, 10),
+            min_coverage: 0.99,
+            max_route_length: 450.0,
+        },
+    ]
+}
@@ -0,0 +1,58 @@
+use crate::error::{Error, Result};
+
+// Accepted names of the coordinate columns, in lower case
+pub const LAT_COLUMNS: &[&str] = &["lat", "latitude"];
+pub const LON_COLUMNS: &[&str] = &["lon", "lng", "long", "longitude"];
+
+// Comma separated text with a header row naming the columns. Blank lines are skipped
+// and column names are matched case insensitively.
+pub struct CsvTable<'a> {
+    header: Vec<String>, // column names in lower case
+    lines: Vec<&'a str>, // data rows as written
+}
+
+// Data row of a table
+pub struct CsvRow<'a> {
+    pub number: usize, // row number counting the header as row 1
+    pub line: &'a str, // the row as written
+    fields: Vec<&'a str>,
+}
+
+impl<'a> CsvTable<'a> {
+    // empty_message is the error when there is not even a header
+    pub fn parse(contents: &'a str, empty_message: &str) -> Result<CsvTable<'a>> {
+        let mut lines = contents.lines().filter(|line| !line.trim().is_empty());
+        let header = lines
+            .next()
+            .ok_or_else(|| Error::validation(empty_message))?
+            .split(',')
+            .map(|name| name.trim().to_lowercase())
+            .collect();
+        Ok(CsvTable {
+            header,
+            lines: lines.collect(),
+        })
+    }
+
+    // Index of the first column with one of the names
+    pub fn column(&self, names: &[&str]) -> Option<usize> {
+        self.header
+            .iter()
+            .position(|name| names.contains(&name.as_str()))
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = CsvRow<'a>> + '_ {
+        self.lines.iter().enumerate().map(|(i, line)| CsvRow {
+            number: i + 2,
+            line,
+            fields: line.split(',').map(|field| field.trim()).collect(),
+        })
+    }
+}
+
+impl<'a> CsvRow<'a> {
+    // Trimmed field of the column, empty when the row is shorter than the header
+    pub fn field(&self, index: usize) -> &'a str {
+        self.fields.get(index).copied().unwrap_or_default()
+    }
+}
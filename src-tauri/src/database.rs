@@ -0,0 +1,123 @@
+use crate::error::{Error, Result};
+use crate::migrations;
+use crate::uav::uav_sql;
+use rusqlite::Connection;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use tauri::PathResolver;
+
+const DATABASE_FILE: &str = "mydatabase.db";
+const DATABASE_FLAG: &str = "--db";
+const DATABASE_ENV: &str = "UAV_ROUTE_DB";
+const CONFIG_FILE: &str = "config.json";
+
+// Connection shared by all tauri commands, opened once at startup
+pub struct Database(Mutex<Connection>);
+
+impl Database {
+    pub fn open(path: &Path) -> Result<Database> {
+        println!("Opening database: {}", path.display());
+        let mut conn = Connection::open(path)?;
+
+        // SQLite keeps foreign keys off unless asked for every connection
+        conn.pragma_update(None, "foreign_keys", true)?;
+
+        let version = migrations::migrate(&mut conn)?;
+        println!("Database schema version: {}", version);
+
+        repair_foreign_keys(&conn)?;
+
+        Ok(Database(Mutex::new(conn)))
+    }
+
+    pub fn connection(&self) -> Result<MutexGuard<'_, Connection>> {
+        self.0
+            .lock()
+            .map_err(|_| Error::database("The database connection lock is poisoned."))
+    }
+}
+
+// Fix references broken while foreign keys were not enforced, then make sure none are left
+pub fn repair_foreign_keys(conn: &Connection) -> Result<()> {
+    let cleared = uav_sql::clear_dangling_cameras(conn)?;
+    if cleared > 0 {
+        eprintln!("Detached missing cameras from {} UAVs", cleared);
+    }
+
+    let mut stmt = conn.prepare("PRAGMA foreign_key_check")?;
+    let violations = stmt
+        .query_map([], |row| {
+            Ok(format!(
+                "{} row {} references missing {}",
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<String>>>()?;
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::database(format!(
+            "Foreign key check failed: {}",
+            violations.join(", ")
+        )))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Config {
+    database_path: Option<PathBuf>,
+}
+
+// Resolve the database location, the first one found wins:
+// --db flag, UAV_ROUTE_DB env var, database_path in config.json, app data directory
+pub fn database_path(path_resolver: &PathResolver) -> Result<PathBuf> {
+    if let Some(path) = path_from_args(std::env::args()) {
+        return Ok(path);
+    }
+
+    if let Some(path) = std::env::var_os(DATABASE_ENV) {
+        return Ok(PathBuf::from(path));
+    }
+
+    if let Some(path) = path_resolver
+        .app_config_dir()
+        .and_then(|dir| path_from_config(&dir.join(CONFIG_FILE)))
+    {
+        return Ok(path);
+    }
+
+    let data_dir = path_resolver
+        .app_data_dir()
+        .ok_or_else(|| Error::database("Cant find the app data directory."))?;
+    std::fs::create_dir_all(&data_dir).map_err(|e| Error::database(e.to_string()))?;
+    Ok(data_dir.join(DATABASE_FILE))
+}
+
+// Accepts both "--db path" and "--db=path"
+fn path_from_args(args: impl Iterator<Item = String>) -> Option<PathBuf> {
+    let mut args = args.skip(1);
+    while let Some(arg) = args.next() {
+        if arg == DATABASE_FLAG {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.strip_prefix(&format!("{}=", DATABASE_FLAG)) {
+            return Some(PathBuf::from(path));
+        }
+    }
+    None
+}
+
+fn path_from_config(config_path: &Path) -> Option<PathBuf> {
+    let contents = std::fs::read_to_string(config_path).ok()?;
+    match serde_json::from_str::<Config>(&contents) {
+        Ok(config) => config.database_path,
+        Err(err) => {
+            eprintln!("Error reading {}: {}", config_path.display(), err);
+            None
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use serde::Serialize;
+use std::fmt;
+
+// Error returned by every tauri command, serialized as {"kind": ..., "message": ...}
+// so the frontend can branch on the kind instead of the text.
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", content = "message")]
+pub enum Error {
+    Database(String),   // failure while opening or querying the database
+    Validation(String), // input rejected before any work was done
+    Algorithm(String),  // the calculation itself failed
+    File(String),       // reading or writing a file failed
+    Cancelled(String),  // the job was cancelled before it finished
+}
+
+impl Error {
+    pub fn database(message: impl Into<String>) -> Error {
+        Error::Database(message.into())
+    }
+
+    pub fn validation(message: impl Into<String>) -> Error {
+        Error::Validation(message.into())
+    }
+
+    pub fn algorithm(message: impl Into<String>) -> Error {
+        Error::Algorithm(message.into())
+    }
+
+    pub fn file(message: impl Into<String>) -> Error {
+        Error::File(message.into())
+    }
+
+    pub fn cancelled(message: impl Into<String>) -> Error {
+        Error::Cancelled(message.into())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Database(message) => write!(f, "Database error: {}", message),
+            Error::Validation(message) => write!(f, "Validation error: {}", message),
+            Error::Algorithm(message) => write!(f, "Algorithm error: {}", message),
+            Error::File(message) => write!(f, "File error: {}", message),
+            Error::Cancelled(message) => write!(f, "Cancelled: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<rusqlite::Error> for Error {
+    fn from(err: rusqlite::Error) -> Error {
+        Error::Database(err.to_string())
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Error {
+        Error::File(err.to_string())
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,29 @@
+use crate::error::Result;
+use crate::export::RouteExport;
+use crate::projection::Projection;
+use std::fmt::Write;
+
+// Waypoint table of the whole route, photo is 1 where a photo is taken
+pub fn to_csv(export: &RouteExport) -> Result<String> {
+    let projection = Projection::from_code(&export.projection)?;
+    let photo_count = export.photo_points().len();
+
+    // Writing to a String can not fail
+    let mut csv = String::from("index,latitude,longitude,altitude,x,y,photo\n");
+    for (i, &(x, y)) in export.route.iter().enumerate() {
+        let (lon, lat) = projection.to_lon_lat(x, y);
+        let photo = (1..=photo_count).contains(&i);
+        let _ = writeln!(
+            csv,
+            "{},{:.8},{:.8},{},{:.3},{:.3},{}",
+            i,
+            lat,
+            lon,
+            export.altitude_at(i),
+            x,
+            y,
+            u8::from(photo)
+        );
+    }
+    Ok(csv)
+}
@@ -0,0 +1,224 @@
+use crate::error::{Error, Result};
+use crate::export::kml::KML_NAMESPACE;
+use crate::export::RouteExport;
+use crate::projection::Projection;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::Write as _;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+const WPML_NAMESPACE: &str = "http://www.dji.com/wpmz/1.0.2";
+
+// Waypoint speed limits accepted by DJI Pilot 2
+const MIN_SPEED: f64 = 1.0;
+const MAX_SPEED: f64 = 15.0;
+const DEFAULT_SPEED: f64 = 10.0;
+
+// Mavic 3 Enterprise, DJI Pilot 2 asks to convert the mission when another drone loads it
+const DRONE_ENUM: u32 = 77;
+const DRONE_SUB_ENUM: u32 = 0;
+
+// One waypoint of a DJI mission, every value is set per waypoint
+#[derive(Debug, Clone, PartialEq)]
+pub struct DjiWaypoint {
+    pub lon: f64,          // degrees
+    pub lat: f64,          // degrees
+    pub height: f64,       // meters above the start point
+    pub speed: f64,        // meters per second
+    pub gimbal_pitch: f64, // degrees, -90 looks straight down
+    pub take_photo: bool,  // take a photo on arrival
+}
+
+// Waypoints of the planned route, the drone takes off and returns home by itself
+pub fn waypoints(export: &RouteExport) -> Result<Vec<DjiWaypoint>> {
+    let projection = Projection::from_code(&export.projection)?;
+    let speed = export
+        .flight_speed
+        .unwrap_or(DEFAULT_SPEED)
+        .clamp(MIN_SPEED, MAX_SPEED);
+
+    Ok(export
+        .photo_points()
+        .iter()
+        .enumerate()
+        .map(|(i, &(x, y))| {
+            let (lon, lat) = projection.to_lon_lat(x, y);
+            DjiWaypoint {
+                lon,
+                lat,
+                height: export.altitude_at(i + 1),
+                speed,
+                gimbal_pitch: export.gimbal_pitch,
+                take_photo: true,
+            }
+        })
+        .collect())
+}
+
+fn mission_config(kml: &mut String, speed: f64) {
+    let _ = write!(
+        kml,
+        "<wpml:missionConfig>\n\
+         <wpml:flyToWaylineMode>safely</wpml:flyToWaylineMode>\n\
+         <wpml:finishAction>goHome</wpml:finishAction>\n\
+         <wpml:exitOnRCLost>executeLostAction</wpml:exitOnRCLost>\n\
+         <wpml:executeRCLostAction>goBack</wpml:executeRCLostAction>\n\
+         <wpml:globalTransitionalSpeed>{}</wpml:globalTransitionalSpeed>\n\
+         <wpml:droneInfo><wpml:droneEnumValue>{}</wpml:droneEnumValue>\
+         <wpml:droneSubEnumValue>{}</wpml:droneSubEnumValue></wpml:droneInfo>\n\
+         </wpml:missionConfig>\n",
+        speed, DRONE_ENUM, DRONE_SUB_ENUM
+    );
+}
+
+// Gimbal pitch and photo actions run when the waypoint is reached
+fn action_group(kml: &mut String, index: usize, waypoint: &DjiWaypoint) {
+    let _ = write!(
+        kml,
+        "<wpml:actionGroup>\n\
+         <wpml:actionGroupId>{index}</wpml:actionGroupId>\n\
+         <wpml:actionGroupStartIndex>{index}</wpml:actionGroupStartIndex>\n\
+         <wpml:actionGroupEndIndex>{index}</wpml:actionGroupEndIndex>\n\
+         <wpml:actionGroupMode>sequence</wpml:actionGroupMode>\n\
+         <wpml:actionTrigger><wpml:actionTriggerType>reachPoint</wpml:actionTriggerType></wpml:actionTrigger>\n\
+         <wpml:action><wpml:actionId>0</wpml:actionId>\
+         <wpml:actionActuatorFunc>gimbalRotate</wpml:actionActuatorFunc>\
+         <wpml:actionActuatorFuncParam>\
+         <wpml:gimbalRotateMode>absoluteAngle</wpml:gimbalRotateMode>\
+         <wpml:gimbalPitchRotateEnable>1</wpml:gimbalPitchRotateEnable>\
+         <wpml:gimbalPitchRotateAngle>{pitch}</wpml:gimbalPitchRotateAngle>\
+         <wpml:gimbalRollRotateEnable>0</wpml:gimbalRollRotateEnable>\
+         <wpml:gimbalYawRotateEnable>0</wpml:gimbalYawRotateEnable>\
+         <wpml:gimbalRotateTimeEnable>0</wpml:gimbalRotateTimeEnable>\
+         <wpml:payloadPositionIndex>0</wpml:payloadPositionIndex>\
+         </wpml:actionActuatorFuncParam></wpml:action>\n",
+        index = index,
+        pitch = waypoint.gimbal_pitch
+    );
+    if waypoint.take_photo {
+        kml.push_str(
+            "<wpml:action><wpml:actionId>1</wpml:actionId>\
+             <wpml:actionActuatorFunc>takePhoto</wpml:actionActuatorFunc>\
+             <wpml:actionActuatorFuncParam>\
+             <wpml:payloadPositionIndex>0</wpml:payloadPositionIndex>\
+             <wpml:useGlobalPayloadLensIndex>0</wpml:useGlobalPayloadLensIndex>\
+             </wpml:actionActuatorFuncParam></wpml:action>\n",
+        );
+    }
+    kml.push_str("</wpml:actionGroup>\n");
+}
+
+fn header(kml: &mut String) {
+    let _ = write!(
+        kml,
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <kml xmlns=\"{}\" xmlns:wpml=\"{}\">\n<Document>\n",
+        KML_NAMESPACE, WPML_NAMESPACE
+    );
+}
+
+// wpmz/template.kml, the editable mission as DJI Pilot 2 shows it
+pub fn template_kml(waypoints: &[DjiWaypoint], speed: f64, created: u128) -> String {
+    let mut kml = String::new();
+    header(&mut kml);
+    let _ = write!(
+        kml,
+        "<wpml:createTime>{created}</wpml:createTime>\n<wpml:updateTime>{created}</wpml:updateTime>\n",
+        created = created
+    );
+    mission_config(&mut kml, speed);
+    let _ = write!(
+        kml,
+        "<Folder>\n\
+         <wpml:templateType>waypoint</wpml:templateType>\n\
+         <wpml:templateId>0</wpml:templateId>\n\
+         <wpml:waylineCoordinateSysParam><wpml:coordinateMode>WGS84</wpml:coordinateMode>\
+         <wpml:heightMode>relativeToStartPoint</wpml:heightMode></wpml:waylineCoordinateSysParam>\n\
+         <wpml:autoFlightSpeed>{}</wpml:autoFlightSpeed>\n\
+         <wpml:gimbalPitchMode>usePointSetting</wpml:gimbalPitchMode>\n\
+         <wpml:globalWaypointHeadingParam><wpml:waypointHeadingMode>followWayline</wpml:waypointHeadingMode></wpml:globalWaypointHeadingParam>\n\
+         <wpml:globalWaypointTurnMode>toPointAndStopWithDiscontinuityCurvature</wpml:globalWaypointTurnMode>\n\
+         <wpml:globalUseStraightLine>1</wpml:globalUseStraightLine>\n",
+        speed
+    );
+    for (i, waypoint) in waypoints.iter().enumerate() {
+        let _ = write!(
+            kml,
+            "<Placemark>\n\
+             <Point><coordinates>{:.8},{:.8}</coordinates></Point>\n\
+             <wpml:index>{}</wpml:index>\n\
+             <wpml:height>{}</wpml:height>\n\
+             <wpml:useGlobalHeight>0</wpml:useGlobalHeight>\n\
+             <wpml:waypointSpeed>{}</wpml:waypointSpeed>\n\
+             <wpml:useGlobalSpeed>0</wpml:useGlobalSpeed>\n\
+             <wpml:useGlobalHeadingParam>1</wpml:useGlobalHeadingParam>\n\
+             <wpml:useGlobalTurnParam>1</wpml:useGlobalTurnParam>\n\
+             <wpml:gimbalPitchAngle>{}</wpml:gimbalPitchAngle>\n",
+            waypoint.lon, waypoint.lat, i, waypoint.height, waypoint.speed, waypoint.gimbal_pitch
+        );
+        action_group(&mut kml, i, waypoint);
+        kml.push_str("</Placemark>\n");
+    }
+    kml.push_str("</Folder>\n</Document>\n</kml>\n");
+    kml
+}
+
+// wpmz/waylines.wpml, the mission the aircraft executes
+pub fn waylines_wpml(waypoints: &[DjiWaypoint], speed: f64) -> String {
+    let mut kml = String::new();
+    header(&mut kml);
+    mission_config(&mut kml, speed);
+    let _ = write!(
+        kml,
+        "<Folder>\n\
+         <wpml:templateId>0</wpml:templateId>\n\
+         <wpml:executeHeightMode>relativeToStartPoint</wpml:executeHeightMode>\n\
+         <wpml:waylineId>0</wpml:waylineId>\n\
+         <wpml:autoFlightSpeed>{}</wpml:autoFlightSpeed>\n",
+        speed
+    );
+    for (i, waypoint) in waypoints.iter().enumerate() {
+        let _ = write!(
+            kml,
+            "<Placemark>\n\
+             <Point><coordinates>{:.8},{:.8}</coordinates></Point>\n\
+             <wpml:index>{}</wpml:index>\n\
+             <wpml:executeHeight>{}</wpml:executeHeight>\n\
+             <wpml:waypointSpeed>{}</wpml:waypointSpeed>\n\
+             <wpml:waypointHeadingParam><wpml:waypointHeadingMode>followWayline</wpml:waypointHeadingMode></wpml:waypointHeadingParam>\n\
+             <wpml:waypointTurnParam><wpml:waypointTurnMode>toPointAndStopWithDiscontinuityCurvature</wpml:waypointTurnMode>\
+             <wpml:waypointTurnDampingDist>0</wpml:waypointTurnDampingDist></wpml:waypointTurnParam>\n",
+            waypoint.lon, waypoint.lat, i, waypoint.height, waypoint.speed
+        );
+        action_group(&mut kml, i, waypoint);
+        kml.push_str("</Placemark>\n");
+    }
+    kml.push_str("</Folder>\n</Document>\n</kml>\n");
+    kml
+}
+
+// KMZ archive with the template and the executable waylines, as DJI Pilot 2 imports it
+pub fn write_kmz(export: &RouteExport, path: &Path) -> Result<()> {
+    let waypoints = waypoints(export)?;
+    let speed = waypoints
+        .first()
+        .map_or(DEFAULT_SPEED, |waypoint| waypoint.speed);
+    let created = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis());
+
+    let zip_error = |e: zip::result::ZipError| Error::file(e.to_string());
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut zip = ZipWriter::new(File::create(path)?);
+    zip.start_file("wpmz/template.kml", options)
+        .map_err(zip_error)?;
+    zip.write_all(template_kml(&waypoints, speed, created).as_bytes())?;
+    zip.start_file("wpmz/waylines.wpml", options)
+        .map_err(zip_error)?;
+    zip.write_all(waylines_wpml(&waypoints, speed).as_bytes())?;
+    zip.finish().map_err(zip_error)?;
+    Ok(())
+}
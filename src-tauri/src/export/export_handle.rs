@@ -0,0 +1,36 @@
+use crate::error::{Error, Result};
+use crate::export::mavlink::MavlinkMission;
+use crate::export::{csv, dji, geojson, gpx, kml, ExportFormat, RouteExport};
+use std::path::Path;
+
+// Write the planned route as an autopilot mission, a review KML or a GIS file,
+// the format follows the file extension
+#[tauri::command]
+pub fn export_route(export: RouteExport, path: String) -> Result<()> {
+    println!("Received export route: {}", path);
+    let path = Path::new(&path);
+    let format = ExportFormat::from_path(path)?;
+    export.check()?;
+
+    let name = path
+        .file_stem()
+        .and_then(|name| name.to_str())
+        .unwrap_or("Mission");
+
+    let contents = match format {
+        ExportFormat::QgcPlan => {
+            serde_json::to_string_pretty(&MavlinkMission::new(&export)?.to_plan())
+                .map_err(|e| Error::file(e.to_string()))?
+        }
+        ExportFormat::QgcWaypoints => MavlinkMission::new(&export)?.to_waypoints(),
+        ExportFormat::DjiKmz => return dji::write_kmz(&export, path),
+        ExportFormat::Kml => kml::to_kml(&export, name)?,
+        ExportFormat::GeoJson => serde_json::to_string_pretty(&geojson::to_geojson(&export)?)
+            .map_err(|e| Error::file(e.to_string()))?,
+        ExportFormat::Gpx => gpx::to_gpx(&export, name)?,
+        ExportFormat::Csv => csv::to_csv(&export)?,
+    };
+
+    std::fs::write(path, contents)?;
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use crate::algorithms;
+use crate::error::Result;
+use crate::export::RouteExport;
+use crate::projection::Projection;
+use serde_json::{json, Value};
+
+fn position(projection: Projection, (x, y): (f64, f64)) -> Value {
+    let (lon, lat) = projection.to_lon_lat(x, y);
+    json!([lon, lat])
+}
+
+// GeoJSON rings are closed, the first position is repeated at the end
+fn ring(projection: Projection, polygon: &[(f64, f64)]) -> Vec<Value> {
+    let mut ring: Vec<Value> = polygon
+        .iter()
+        .map(|&point| position(projection, point))
+        .collect();
+    if let (Some(first), Some(last)) = (polygon.first(), polygon.last()) {
+        if first != last {
+            ring.push(position(projection, *first));
+        }
+    }
+    ring
+}
+
+// FeatureCollection in WGS84: one Polygon per survey area, a Point per photo in the order
+// they are taken and the route LineString carrying the mission metrics
+pub fn to_geojson(export: &RouteExport) -> Result<Value> {
+    let projection = Projection::from_code(&export.projection)?;
+    let photo_points = export.photo_points();
+    let mut features = Vec::new();
+
+    for (i, polygon) in export.polygons.iter().enumerate() {
+        features.push(json!({
+            "type": "Feature",
+            "properties": { "kind": "area", "index": i },
+            "geometry": { "type": "Polygon", "coordinates": [ring(projection, polygon)] },
+        }));
+    }
+
+    for (i, &point) in photo_points.iter().enumerate() {
+        let (lon, lat) = projection.to_lon_lat(point.0, point.1);
+        let altitude = export.altitude_at(i + 1);
+        features.push(json!({
+            "type": "Feature",
+            "properties": { "kind": "photo", "index": i, "altitude": altitude },
+            "geometry": { "type": "Point", "coordinates": [lon, lat, altitude] },
+        }));
+    }
+
+    let coordinates: Vec<Value> = export
+        .route
+        .iter()
+        .map(|&point| position(projection, point))
+        .collect();
+    features.push(json!({
+        "type": "Feature",
+        "properties": {
+            "kind": "route",
+            "projection": projection.code(),
+            "altitude": export.altitude,
+            "length": algorithms::calculate_distance(export.route.clone())?,
+            "duration": export.duration,
+            "flight_speed": export.flight_speed,
+            "photo_count": photo_points.len(),
+        },
+        "geometry": { "type": "LineString", "coordinates": coordinates },
+    }));
+
+    Ok(json!({ "type": "FeatureCollection", "features": features }))
+}
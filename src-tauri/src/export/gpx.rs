@@ -0,0 +1,33 @@
+use crate::error::Result;
+use crate::export::RouteExport;
+use crate::projection::Projection;
+use quick_xml::escape::escape;
+use std::fmt::Write;
+
+// GPX 1.1 route, every route point at its flight altitude
+pub fn to_gpx(export: &RouteExport, name: &str) -> Result<String> {
+    let projection = Projection::from_code(&export.projection)?;
+
+    // Writing to a String can not fail
+    let mut gpx = String::new();
+    let _ = write!(
+        gpx,
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <gpx version=\"1.1\" creator=\"UAV route planner\" xmlns=\"http://www.topografix.com/GPX/1/1\">\n\
+         <rte>\n<name>{}</name>\n",
+        escape(name)
+    );
+    for (i, &(x, y)) in export.route.iter().enumerate() {
+        let (lon, lat) = projection.to_lon_lat(x, y);
+        let _ = writeln!(
+            gpx,
+            "<rtept lat=\"{:.8}\" lon=\"{:.8}\"><ele>{}</ele><name>WP{}</name></rtept>",
+            lat,
+            lon,
+            export.altitude_at(i),
+            i
+        );
+    }
+    gpx.push_str("</rte>\n</gpx>\n");
+    Ok(gpx)
+}
@@ -0,0 +1,121 @@
+use crate::error::Result;
+use crate::export::RouteExport;
+use crate::projection::Projection;
+use quick_xml::escape::escape;
+use std::fmt::Write;
+
+pub const KML_NAMESPACE: &str = "http://www.opengis.net/kml/2.2";
+
+// KML coordinates tuples "lon,lat,alt" of projected points, altitude gives the point's by index
+pub fn coordinates(
+    points: &[(f64, f64)],
+    projection: Projection,
+    altitude: impl Fn(usize) -> f64,
+) -> String {
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, &(x, y))| {
+            let (lon, lat) = projection.to_lon_lat(x, y);
+            format!("{:.8},{:.8},{}", lon, lat, altitude(i))
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+// Closed ring as KML wants it, the first point repeated at the end
+fn ring(points: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    let mut ring = points.to_vec();
+    if let (Some(&first), Some(&last)) = (points.first(), points.last()) {
+        if first != last {
+            ring.push(first);
+        }
+    }
+    ring
+}
+
+// Ground rectangle covered by a photo taken at the center, sides follow the discretization
+// direction like the discretize_area grid
+fn footprint(center: (f64, f64), size: (f64, f64), direction_degrees: f64) -> Vec<(f64, f64)> {
+    let (sin, cos) = direction_degrees.to_radians().sin_cos();
+    let (half_width, half_height) = (size.0 / 2.0, size.1 / 2.0);
+    [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+        .iter()
+        .map(|(sx, sy)| {
+            let (x, y) = (sx * half_width, sy * half_height);
+            (center.0 + x * cos - y * sin, center.1 + x * sin + y * cos)
+        })
+        .collect()
+}
+
+fn polygon_placemark(kml: &mut String, name: &str, style: &str, coordinates: &str) {
+    let _ = write!(
+        kml,
+        "<Placemark><name>{}</name><styleUrl>#{}</styleUrl><Polygon><outerBoundaryIs><LinearRing>\
+         <coordinates>{}</coordinates></LinearRing></outerBoundaryIs></Polygon></Placemark>\n",
+        escape(name),
+        style,
+        coordinates
+    );
+}
+
+// Plain KML for reviewing a plan in Google Earth: survey area, photo footprints and route
+pub fn to_kml(export: &RouteExport, name: &str) -> Result<String> {
+    let projection = Projection::from_code(&export.projection)?;
+
+    // Writing to a String can not fail
+    let mut kml = String::new();
+    let _ = write!(
+        kml,
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<kml xmlns=\"{}\">\n<Document>\n<name>{}</name>\n",
+        KML_NAMESPACE,
+        escape(name)
+    );
+    kml.push_str(
+        "<Style id=\"area\"><LineStyle><color>ff00ffff</color><width>2</width></LineStyle>\
+         <PolyStyle><color>4000ffff</color></PolyStyle></Style>\n\
+         <Style id=\"footprint\"><LineStyle><color>ffff8800</color><width>1</width></LineStyle>\
+         <PolyStyle><fill>0</fill></PolyStyle></Style>\n\
+         <Style id=\"route\"><LineStyle><color>ff0000ff</color><width>3</width></LineStyle></Style>\n",
+    );
+
+    kml.push_str("<Folder><name>Area</name>\n");
+    for (i, polygon) in export.polygons.iter().enumerate() {
+        let coordinates = coordinates(&ring(polygon), projection, |_| 0.0);
+        polygon_placemark(&mut kml, &format!("Area {}", i + 1), "area", &coordinates);
+    }
+    kml.push_str("</Folder>\n");
+
+    if let Some(camera) = &export.camera {
+        kml.push_str("<Folder><name>Photo footprints</name>\n");
+        for (i, photo) in export.photos.iter().enumerate() {
+            // Full photo without the overlap, taken at the altitude the route reaches it
+            let altitude = export
+                .route
+                .iter()
+                .position(|point| point == photo)
+                .map_or(export.altitude, |index| export.altitude_at(index));
+            let size = camera.photo_size(altitude, 0.0);
+            let corners = ring(&footprint(*photo, size, export.direction));
+            let coordinates = coordinates(&corners, projection, |_| 0.0);
+            polygon_placemark(
+                &mut kml,
+                &format!("Photo {}", i + 1),
+                "footprint",
+                &coordinates,
+            );
+        }
+        kml.push_str("</Folder>\n");
+    }
+
+    let _ = write!(
+        kml,
+        "<Placemark><name>Route</name><styleUrl>#route</styleUrl><LineString>\
+         <altitudeMode>relativeToGround</altitudeMode><coordinates>{}</coordinates>\
+         </LineString></Placemark>\n",
+        coordinates(&export.route, projection, |i| export.altitude_at(i))
+    );
+
+    kml.push_str("</Document>\n</kml>\n");
+    Ok(kml)
+}
@@ -0,0 +1,200 @@
+use crate::error::Result;
+use crate::export::RouteExport;
+use crate::projection::Projection;
+use serde_json::{json, Value};
+use std::fmt::Write;
+
+// MAVLink commands used by exported missions
+pub const MAV_CMD_NAV_WAYPOINT: u16 = 16;
+pub const MAV_CMD_NAV_RETURN_TO_LAUNCH: u16 = 20;
+pub const MAV_CMD_NAV_TAKEOFF: u16 = 22;
+pub const MAV_CMD_DO_CHANGE_SPEED: u16 = 178;
+pub const MAV_CMD_DO_DIGICAM_CONTROL: u16 = 203;
+pub const MAV_CMD_DO_SET_CAM_TRIGG_DIST: u16 = 206;
+
+// Coordinate frames, altitudes of mission items are relative to home
+pub const MAV_FRAME_GLOBAL: u8 = 0;
+pub const MAV_FRAME_GLOBAL_RELATIVE_ALT: u8 = 3;
+pub const MAV_FRAME_MISSION: u8 = 2;
+
+const MAV_AUTOPILOT_GENERIC: u8 = 0;
+const MAV_TYPE_QUADROTOR: u8 = 2;
+
+// One MAVLink mission item, params 5 to 7 are latitude, longitude and altitude
+#[derive(Debug, Clone, PartialEq)]
+pub struct MissionItem {
+    pub command: u16,
+    pub frame: u8,
+    pub params: [f64; 7],
+}
+
+impl MissionItem {
+    fn navigation(command: u16, (lon, lat): (f64, f64), altitude: f64) -> MissionItem {
+        MissionItem {
+            command,
+            frame: MAV_FRAME_GLOBAL_RELATIVE_ALT,
+            params: [0.0, 0.0, 0.0, 0.0, lat, lon, altitude],
+        }
+    }
+
+    fn action(command: u16, params: [f64; 7]) -> MissionItem {
+        MissionItem {
+            command,
+            frame: MAV_FRAME_MISSION,
+            params,
+        }
+    }
+}
+
+// Survey flight as MAVLink mission items: the home position, then takeoff over it,
+// a waypoint with a photo trigger for every photo position and return to launch.
+// With a trigger plan the photo positions are replaced by continuous flight lines
+// with distance triggering switched on between their start and stop.
+#[derive(Debug, Clone)]
+pub struct MavlinkMission {
+    pub home: (f64, f64), // longitude and latitude of the start point
+    pub altitude: f64,    // takeoff altitude above home in meters
+    pub flight_speed: Option<f64>,
+    pub items: Vec<MissionItem>,
+}
+
+impl MavlinkMission {
+    pub fn new(export: &RouteExport) -> Result<MavlinkMission> {
+        let projection = Projection::from_code(&export.projection)?;
+        let start_point = export.start_point()?;
+        let (altitude, flight_speed) = (export.altitude, export.flight_speed);
+
+        let home = projection.to_lon_lat(start_point.0, start_point.1);
+        let mut items = vec![MissionItem::navigation(MAV_CMD_NAV_TAKEOFF, home, altitude)];
+        if let Some(speed) = flight_speed {
+            // Ground speed, unchanged throttle
+            items.push(MissionItem::action(
+                MAV_CMD_DO_CHANGE_SPEED,
+                [1.0, speed, -1.0, 0.0, 0.0, 0.0, 0.0],
+            ));
+        }
+        match &export.trigger_plan {
+            Some(trigger_plan) => {
+                for line in &trigger_plan.lines {
+                    items.push(MissionItem::navigation(
+                        MAV_CMD_NAV_WAYPOINT,
+                        projection.to_lon_lat(line.start.0, line.start.1),
+                        export.region_altitude(line.polygon),
+                    ));
+                    // Trigger right away and then every trigger distance
+                    items.push(MissionItem::action(
+                        MAV_CMD_DO_SET_CAM_TRIGG_DIST,
+                        [line.trigger_distance, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0],
+                    ));
+                    items.push(MissionItem::navigation(
+                        MAV_CMD_NAV_WAYPOINT,
+                        projection.to_lon_lat(line.stop.0, line.stop.1),
+                        export.region_altitude(line.polygon),
+                    ));
+                    items.push(MissionItem::action(MAV_CMD_DO_SET_CAM_TRIGG_DIST, [0.0; 7]));
+                }
+            }
+            None => {
+                for (i, point) in export.photo_points().iter().enumerate() {
+                    items.push(MissionItem::navigation(
+                        MAV_CMD_NAV_WAYPOINT,
+                        projection.to_lon_lat(point.0, point.1),
+                        export.altitude_at(i + 1),
+                    ));
+                    // Shoot one photo on arrival
+                    items.push(MissionItem::action(
+                        MAV_CMD_DO_DIGICAM_CONTROL,
+                        [0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0],
+                    ));
+                }
+            }
+        }
+        items.push(MissionItem::action(MAV_CMD_NAV_RETURN_TO_LAUNCH, [0.0; 7]));
+
+        Ok(MavlinkMission {
+            home,
+            altitude,
+            flight_speed,
+            items,
+        })
+    }
+
+    // QGroundControl .plan JSON
+    pub fn to_plan(&self) -> Value {
+        let items: Vec<Value> = self
+            .items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                let mut value = json!({
+                    "type": "SimpleItem",
+                    "autoContinue": true,
+                    "command": item.command,
+                    "doJumpId": i + 1,
+                    "frame": item.frame,
+                    "params": item.params,
+                });
+                if item.frame == MAV_FRAME_GLOBAL_RELATIVE_ALT {
+                    value["Altitude"] = json!(item.params[6]);
+                    value["AltitudeMode"] = json!(1);
+                    value["AMSLAltAboveTerrain"] = Value::Null;
+                }
+                value
+            })
+            .collect();
+
+        let mut mission = json!({
+            "version": 2,
+            "firmwareType": MAV_AUTOPILOT_GENERIC,
+            "vehicleType": MAV_TYPE_QUADROTOR,
+            "globalPlanAltitudeMode": 1,
+            "plannedHomePosition": [self.home.1, self.home.0, 0.0],
+            "items": items,
+        });
+        if let Some(speed) = self.flight_speed {
+            mission["cruiseSpeed"] = json!(speed);
+            mission["hoverSpeed"] = json!(speed);
+        }
+
+        json!({
+            "fileType": "Plan",
+            "version": 1,
+            "groundStation": "QGroundControl",
+            "mission": mission,
+            "geoFence": { "circles": [], "polygons": [], "version": 2 },
+            "rallyPoints": { "points": [], "version": 2 },
+        })
+    }
+
+    // Tab separated QGC WPL 110 text, the first line after the header is the home position:
+    // index, current, frame, command, param1-4, latitude, longitude, altitude, autocontinue
+    pub fn to_waypoints(&self) -> String {
+        let mut text = String::from("QGC WPL 110\n");
+        let home = MissionItem {
+            command: MAV_CMD_NAV_WAYPOINT,
+            frame: MAV_FRAME_GLOBAL,
+            params: [0.0, 0.0, 0.0, 0.0, self.home.1, self.home.0, 0.0],
+        };
+
+        for (i, item) in std::iter::once(&home).chain(&self.items).enumerate() {
+            let p = item.params;
+            // Writing to a String can not fail
+            let _ = writeln!(
+                text,
+                "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{:.8}\t{:.8}\t{:.6}\t1",
+                i,
+                u8::from(i == 0),
+                item.frame,
+                item.command,
+                p[0],
+                p[1],
+                p[2],
+                p[3],
+                p[4],
+                p[5],
+                p[6]
+            );
+        }
+        text
+    }
+}
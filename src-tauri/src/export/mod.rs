@@ -0,0 +1,246 @@
+use crate::camera::Camera;
+use crate::error::{Error, Result};
+use crate::trigger::TriggerPlan;
+use serde::Deserialize;
+use std::path::Path;
+pub mod csv;
+pub mod dji;
+pub mod export_handle;
+pub mod geojson;
+pub mod gpx;
+pub mod kml;
+pub mod mavlink;
+
+// File formats a planned route can be exported to, picked by the file extension
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    QgcPlan,      // QGroundControl .plan JSON
+    QgcWaypoints, // QGC WPL 110 text, .waypoints
+    DjiKmz,       // DJI WPML waypoint mission, .kmz
+    Kml,          // area, photo footprints and route for Google Earth
+    GeoJson,      // FeatureCollection with area, photo points and route
+    Gpx,          // GPX route
+    Csv,          // waypoint table
+}
+
+impl ExportFormat {
+    pub fn from_path(path: &Path) -> Result<ExportFormat> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+
+        match extension.as_str() {
+            "plan" => Ok(ExportFormat::QgcPlan),
+            "waypoints" | "txt" => Ok(ExportFormat::QgcWaypoints),
+            "kmz" => Ok(ExportFormat::DjiKmz),
+            "kml" => Ok(ExportFormat::Kml),
+            "geojson" | "json" => Ok(ExportFormat::GeoJson),
+            "gpx" => Ok(ExportFormat::Gpx),
+            "csv" => Ok(ExportFormat::Csv),
+            _ => Err(Error::validation(format!(
+                "Unsupported export format: {}",
+                path.display()
+            ))),
+        }
+    }
+}
+
+// Everything the exporters need to know about a planned route,
+// coordinates are in meters of the planning projection
+#[derive(Debug, Deserialize)]
+pub struct RouteExport {
+    pub projection: String,        // planning projection, e.g. EPSG:32635
+    pub route: Vec<(f64, f64)>,    // calculated route, starts and ends at the start point
+    pub altitude: f64,             // takeoff altitude above the start point in meters
+    pub flight_speed: Option<f64>, // meters per second, autopilot default when missing
+    #[serde(default)]
+    pub altitudes: Vec<f64>, // flight altitude of every route point, altitude when empty
+    #[serde(default)]
+    pub region_altitudes: Vec<f64>, // flight altitude of every polygon, for trigger lines
+    #[serde(default)]
+    pub polygons: Vec<Vec<(f64, f64)>>, // survey area polygons
+    #[serde(default)]
+    pub photos: Vec<(f64, f64)>, // photo centers of the discretized area
+    #[serde(default)]
+    pub direction: f64, // discretization direction in degrees
+    #[serde(default)]
+    pub camera: Option<Camera>, // camera taking the photos, for photo footprints
+    #[serde(default)]
+    pub duration: Option<f64>, // estimated mission duration in seconds
+    #[serde(default)]
+    pub trigger_plan: Option<TriggerPlan>, // fly continuous lines triggering by distance
+    #[serde(default = "nadir_gimbal_pitch")]
+    pub gimbal_pitch: f64, // gimbal pitch in degrees, -90 looks straight down
+}
+
+fn nadir_gimbal_pitch() -> f64 {
+    -90.0
+}
+
+impl RouteExport {
+    pub fn check(&self) -> Result<()> {
+        if self.route.len() < 2 {
+            return Err(Error::validation("Route is empty, nothing to export."));
+        }
+        if self.altitude <= 0.0 {
+            return Err(Error::validation("Altitude should be greater than zero"));
+        }
+        if !self.altitudes.is_empty() && self.altitudes.len() != self.route.len() {
+            return Err(Error::validation(format!(
+                "Expected an altitude for each of the {} route points, got {}",
+                self.route.len(),
+                self.altitudes.len()
+            )));
+        }
+        if self
+            .altitudes
+            .iter()
+            .chain(&self.region_altitudes)
+            .any(|&altitude| altitude <= 0.0)
+        {
+            return Err(Error::validation("Altitude should be greater than zero"));
+        }
+        if self.flight_speed.map_or(false, |speed| speed <= 0.0) {
+            return Err(Error::validation(
+                "Flight speed should be greater than zero",
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn start_point(&self) -> Result<(f64, f64)> {
+        self.route
+            .first()
+            .copied()
+            .ok_or_else(|| Error::validation("Route is empty, nothing to export."))
+    }
+
+    // Flight altitude at the route point with the index
+    pub fn altitude_at(&self, index: usize) -> f64 {
+        self.altitudes.get(index).copied().unwrap_or(self.altitude)
+    }
+
+    // Flight altitude over the polygon with the index
+    pub fn region_altitude(&self, polygon: usize) -> f64 {
+        self.region_altitudes
+            .get(polygon)
+            .copied()
+            .unwrap_or(self.altitude)
+    }
+
+    // Route points between takeoff and landing at the start point, a photo is taken at each.
+    // The photo point with index i is the route point i + 1.
+    pub fn photo_points(&self) -> &[(f64, f64)] {
+        match self.route.as_slice() {
+            [first, photos @ .., last] if first == last => photos,
+            [_, photos @ ..] => photos,
+            [] => &[],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::SurveyRegion;
+    use crate::export::mavlink::{MavlinkMission, MAV_CMD_NAV_WAYPOINT};
+    use crate::trigger::region_trigger_plan;
+
+    // Two square regions side by side, the first flown at 50 m and the second at 80 m
+    fn two_regions() -> RouteExport {
+        let polygons = vec![
+            vec![(0.0, 0.0), (100.0, 0.0), (100.0, 100.0), (0.0, 100.0)],
+            vec![(200.0, 0.0), (300.0, 0.0), (300.0, 100.0), (200.0, 100.0)],
+        ];
+        let surveys = polygons
+            .iter()
+            .map(|polygon| SurveyRegion {
+                polygon: polygon.clone(),
+                photo_width: 50.0,
+                photo_height: 50.0,
+                direction_degrees: 0.0,
+            })
+            .collect();
+        let start_point = (500_000.0, 4_000_000.0);
+        let offset = |(x, y): (f64, f64)| (start_point.0 + x, start_point.1 + y);
+        RouteExport {
+            projection: "EPSG:32635".to_string(),
+            route: vec![(0.0, 0.0), (50.0, 50.0), (250.0, 50.0), (0.0, 0.0)]
+                .into_iter()
+                .map(offset)
+                .collect(),
+            altitude: 50.0,
+            flight_speed: Some(5.0),
+            altitudes: vec![50.0, 50.0, 80.0, 50.0],
+            region_altitudes: vec![50.0, 80.0],
+            polygons: Vec::new(),
+            photos: Vec::new(),
+            direction: 0.0,
+            camera: None,
+            duration: None,
+            trigger_plan: Some(region_trigger_plan(surveys, (0.0, 0.0), None).unwrap()),
+            gimbal_pitch: -90.0,
+        }
+    }
+
+    fn waypoint_altitudes(mission: &MavlinkMission) -> Vec<f64> {
+        mission
+            .items
+            .iter()
+            .filter(|item| item.command == MAV_CMD_NAV_WAYPOINT)
+            .map(|item| item.params[6])
+            .collect()
+    }
+
+    #[test]
+    fn regions_are_exported_at_their_altitudes() {
+        let mut export = two_regions();
+        export.check().unwrap();
+
+        let trigger_plan = export.trigger_plan.take().unwrap();
+        let points = MavlinkMission::new(&export).unwrap();
+        assert_eq!(waypoint_altitudes(&points), vec![50.0, 80.0]);
+
+        let heights: Vec<f64> = dji::waypoints(&export)
+            .unwrap()
+            .iter()
+            .map(|waypoint| waypoint.height)
+            .collect();
+        assert_eq!(heights, vec![50.0, 80.0]);
+
+        let rows: Vec<String> = csv::to_csv(&export)
+            .unwrap()
+            .lines()
+            .skip(1)
+            .map(|row| row.split(',').nth(3).unwrap().to_string())
+            .collect();
+        assert_eq!(rows, vec!["50", "50", "80", "50"]);
+
+        let gpx = gpx::to_gpx(&export, "regions").unwrap();
+        assert_eq!(gpx.matches("<ele>80</ele>").count(), 1);
+        assert_eq!(gpx.matches("<ele>50</ele>").count(), 3);
+
+        let kml = kml::to_kml(&export, "regions").unwrap();
+        assert_eq!(kml.matches(",80 ").count(), 1);
+
+        // Each trigger line flies at the altitude of its polygon
+        let expected: Vec<f64> = trigger_plan
+            .lines
+            .iter()
+            .flat_map(|line| vec![export.region_altitudes[line.polygon]; 2])
+            .collect();
+        assert!(expected.contains(&50.0) && expected.contains(&80.0));
+        export.trigger_plan = Some(trigger_plan);
+        let lines = MavlinkMission::new(&export).unwrap();
+        assert_eq!(waypoint_altitudes(&lines), expected);
+    }
+
+    #[test]
+    fn every_route_point_needs_an_altitude() {
+        let mut export = two_regions();
+        export.altitudes.pop();
+        assert!(export.check().is_err());
+    }
+}
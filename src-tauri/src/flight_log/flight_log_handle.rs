@@ -0,0 +1,52 @@
+use crate::database::Database;
+use crate::error::{Error, Result};
+use crate::flight_log::{self, track_import, FlightComparison};
+use crate::mission::mission_handle::mission_equipment;
+use crate::mission::mission_sql;
+use crate::uav::uav_sql;
+use std::path::Path;
+use tauri::State;
+
+// Compare a recorded flight (CSV or GPX) with the planned route of the mission.
+// With calibrate set the measured speeds are stored on the uav for future estimates.
+#[tauri::command]
+pub fn import_flight_log(
+    mission_id: u64,
+    path: String,
+    calibrate: bool,
+    database: State<'_, Database>,
+) -> Result<FlightComparison> {
+    println!("Received flight log: {}", path);
+    let track = track_import::read_track(Path::new(&path))?;
+
+    let conn = database.connection()?;
+    let mission = mission_sql::get_mission(mission_id, &conn)?
+        .ok_or_else(|| Error::validation(format!("Mission {} does not exist.", mission_id)))?;
+    let (uav, camera, region_cameras) = mission_equipment(&mission, &conn)?;
+
+    let comparison = flight_log::compare(&mission, &uav, &camera, &region_cameras, &track)?;
+
+    if calibrate {
+        let (uav_id, flight_speed, takeoff_speed) = match (
+            mission.uav_id,
+            comparison.flight_speed,
+            comparison.takeoff_speed,
+        ) {
+            (Some(uav_id), Some(flight_speed), Some(takeoff_speed)) => {
+                (uav_id, flight_speed, takeoff_speed)
+            }
+            _ => {
+                return Err(Error::validation(
+                    "Flight log has no altitude or timing data to calibrate the UAV.",
+                ))
+            }
+        };
+        println!(
+            "Calibrating UAV {}: flight speed {:.2} m/s, takeoff speed {:.2} m/s",
+            uav_id, flight_speed, takeoff_speed
+        );
+        uav_sql::update_speeds(uav_id, flight_speed, takeoff_speed, &conn)?;
+    }
+
+    Ok(comparison)
+}
@@ -0,0 +1,162 @@
+use crate::algorithms::segment_distance;
+use crate::camera::Camera;
+use crate::error::{Error, Result};
+use crate::mission::Mission;
+use crate::projection::Projection;
+use crate::uav::Uav;
+use serde::Serialize;
+use std::collections::HashMap;
+pub mod flight_log_handle;
+pub mod track_import;
+
+// Share of the mission altitude that marks the end of the takeoff
+const TAKEOFF_ALTITUDE_RATIO: f64 = 0.95;
+
+// Position recorded by the uav during a flight
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct TrackPoint {
+    pub time: f64,             // seconds since the unix epoch
+    pub lat: f64,              // latitude in degrees
+    pub lon: f64,              // longitude in degrees
+    pub altitude: Option<f64>, // altitude in meters, if recorded
+}
+
+// Deviation of the flown track from one planned leg
+#[derive(Debug, Serialize)]
+pub struct LegDeviation {
+    pub from: (f64, f64),            // leg start waypoint
+    pub to: (f64, f64),              // leg end waypoint
+    pub samples: usize,              // track points closest to this leg
+    pub mean_cross_track_error: f64, // meters
+    pub max_cross_track_error: f64,  // meters
+}
+
+// Planned mission compared with the flight that was actually flown
+#[derive(Debug, Serialize)]
+pub struct FlightComparison {
+    pub legs: Vec<LegDeviation>,
+    pub missed_photos: Vec<(f64, f64)>, // photo centers the track never came close to
+    pub photo_count: usize,             // planned photo centers
+    pub planned_duration: f64,          // seconds
+    pub actual_duration: f64,           // seconds
+    pub takeoff_duration: Option<f64>,  // seconds to reach the mission altitude
+    pub flight_speed: Option<f64>,      // effective speed over the route in meters per second
+    pub takeoff_speed: Option<f64>,     // effective climb speed in meters per second
+}
+
+// Compare a recorded track with the planned route of the mission.
+// Each track point counts towards the leg it is closest to, a photo is missed when
+// the track never passes closer than half a photo of its polygon to its center.
+pub fn compare(
+    mission: &Mission,
+    uav: &Uav,
+    camera: &Camera,
+    region_cameras: &HashMap<u64, Camera>,
+    track: &[TrackPoint],
+) -> Result<FlightComparison> {
+    if mission.waypoints.len() < 2 {
+        return Err(Error::validation(
+            "Mission has no planned route, plan it first.",
+        ));
+    }
+    let (first, last) = match (track.first(), track.last()) {
+        (Some(first), Some(last)) if track.len() >= 2 => (first, last),
+        _ => {
+            return Err(Error::validation(
+                "Flight log must contain at least two timestamped positions.",
+            ))
+        }
+    };
+
+    let projection = Projection::from_code(&mission.projection)?;
+    let flown: Vec<(f64, f64)> = track
+        .iter()
+        .map(|point| projection.from_lon_lat(point.lon, point.lat))
+        .collect();
+
+    let mut legs: Vec<LegDeviation> = mission
+        .waypoints
+        .windows(2)
+        .map(|leg| LegDeviation {
+            from: leg[0],
+            to: leg[1],
+            samples: 0,
+            mean_cross_track_error: 0.0,
+            max_cross_track_error: 0.0,
+        })
+        .collect();
+    for point in &flown {
+        let (leg, error) = legs
+            .iter()
+            .map(|leg| segment_distance(point, &leg.from, &leg.to))
+            .enumerate()
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .ok_or_else(|| Error::algorithm("Mission route has no legs."))?;
+        let leg = &mut legs[leg];
+        leg.samples += 1;
+        leg.mean_cross_track_error += error;
+        leg.max_cross_track_error = leg.max_cross_track_error.max(error);
+    }
+    for leg in legs.iter_mut().filter(|leg| leg.samples > 0) {
+        leg.mean_cross_track_error /= leg.samples as f64;
+    }
+
+    let regions = mission.survey_regions(camera, region_cameras)?;
+    let photos: Vec<((f64, f64), f64)> = mission
+        .discretize(camera, region_cameras)?
+        .into_iter()
+        .zip(regions.iter())
+        .flat_map(|(grid, region)| {
+            let tolerance = region.photo_width.min(region.photo_height) / 2.0;
+            grid.into_iter()
+                .flatten()
+                .map(move |photo| (photo, tolerance))
+        })
+        .collect();
+    let missed_photos: Vec<(f64, f64)> = photos
+        .iter()
+        .filter(|(photo, tolerance)| {
+            flown
+                .windows(2)
+                .all(|segment| segment_distance(photo, &segment[0], &segment[1]) > *tolerance)
+        })
+        .map(|(photo, _)| *photo)
+        .collect();
+    let profile = mission.flight_profile(uav, camera, region_cameras)?;
+    let climb_altitude = profile.climb_altitude;
+
+    // Takeoff ends once the uav climbs close to the mission altitude above its first fix
+    let takeoff = first.altitude.and_then(|ground| {
+        track.iter().find_map(|point| {
+            let climb = point.altitude? - ground;
+            (climb >= TAKEOFF_ALTITUDE_RATIO * climb_altitude)
+                .then_some((point.time - first.time, climb))
+        })
+    });
+    let takeoff_duration = takeoff.map(|(duration, _)| duration);
+    let takeoff_speed = takeoff
+        .filter(|(duration, _)| *duration > 0.0)
+        .map(|(duration, climb)| climb / duration);
+
+    // The rest of the flight is spent on the route, matching Uav::mission_duration
+    let actual_duration = last.time - first.time;
+    let route_duration =
+        actual_duration - takeoff_duration.unwrap_or(climb_altitude / uav.takeoff_speed);
+    let flight_speed = (route_duration > 0.0 && mission.route_length > 0.0)
+        .then(|| mission.route_length / route_duration);
+
+    Ok(FlightComparison {
+        legs,
+        missed_photos,
+        photo_count: photos.len(),
+        planned_duration: uav.mission_duration(
+            mission.route_length,
+            climb_altitude,
+            profile.ground_speed,
+        ),
+        actual_duration,
+        takeoff_duration,
+        flight_speed,
+        takeoff_speed,
+    })
+}
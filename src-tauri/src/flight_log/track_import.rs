@@ -0,0 +1,167 @@
+use crate::csv_table::{CsvTable, LAT_COLUMNS, LON_COLUMNS};
+use crate::error::{Error, Result};
+use crate::flight_log::TrackPoint;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::path::Path;
+
+// Read a recorded track, the format is taken from the file extension
+pub fn read_track(path: &Path) -> Result<Vec<TrackPoint>> {
+    let contents = std::fs::read_to_string(path)?;
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+
+    let mut track = match extension.as_str() {
+        "csv" => parse_csv(&contents)?,
+        "gpx" => parse_gpx(&contents)?,
+        _ => {
+            return Err(Error::validation(format!(
+                "Unsupported flight log format: {}",
+                path.display()
+            )))
+        }
+    };
+
+    if track.len() < 2 {
+        return Err(Error::validation(
+            "Flight log must contain at least two timestamped positions.",
+        ));
+    }
+    track.sort_by(|a, b| a.time.total_cmp(&b.time));
+    Ok(track)
+}
+
+// CSV with a header naming the columns: time, lat and lon are required, alt is optional.
+// Common aliases such as timestamp, latitude, longitude and altitude are accepted too.
+pub fn parse_csv(contents: &str) -> Result<Vec<TrackPoint>> {
+    let table = CsvTable::parse(contents, "Flight log is empty.")?;
+    let time_column = table.column(&["time", "timestamp", "datetime"]);
+    let lat_column = table.column(LAT_COLUMNS);
+    let lon_column = table.column(LON_COLUMNS);
+    let alt_column = table.column(&["alt", "altitude", "ele", "elevation"]);
+
+    let (time_column, lat_column, lon_column) = match (time_column, lat_column, lon_column) {
+        (Some(time), Some(lat), Some(lon)) => (time, lat, lon),
+        _ => {
+            return Err(Error::validation(
+                "Flight log CSV needs time, lat and lon columns.",
+            ))
+        }
+    };
+
+    let mut track = Vec::new();
+    for row in table.rows() {
+        let invalid = || {
+            Error::validation(format!(
+                "Invalid flight log row {}: {}",
+                row.number, row.line
+            ))
+        };
+
+        track.push(TrackPoint {
+            time: parse_time(row.field(time_column)).ok_or_else(invalid)?,
+            lat: row.field(lat_column).parse().map_err(|_| invalid())?,
+            lon: row.field(lon_column).parse().map_err(|_| invalid())?,
+            altitude: alt_column.and_then(|alt| row.field(alt).parse().ok()),
+        });
+    }
+    Ok(track)
+}
+
+// Track points of every track segment in a GPX file, points without time are skipped
+pub fn parse_gpx(contents: &str) -> Result<Vec<TrackPoint>> {
+    let invalid = |e: quick_xml::Error| Error::validation(format!("Invalid GPX: {}", e));
+
+    let mut reader = Reader::from_str(contents);
+    reader.trim_text(true);
+
+    let mut track = Vec::new();
+    // Point being read and the name of the child element whose text comes next
+    let mut point: Option<(f64, f64, Option<f64>, Option<f64>)> = None;
+    let mut element = Vec::new();
+
+    loop {
+        match reader.read_event().map_err(invalid)? {
+            Event::Start(e) if e.local_name().as_ref() == b"trkpt" => {
+                let coordinate = |name: &str| -> Result<f64> {
+                    e.try_get_attribute(name)
+                        .map_err(|e| invalid(e.into()))?
+                        .and_then(|attribute| attribute.unescape_value().ok()?.parse().ok())
+                        .ok_or_else(|| Error::validation(format!("GPX trkpt without {}", name)))
+                };
+                point = Some((coordinate("lat")?, coordinate("lon")?, None, None));
+            }
+            Event::Start(e) => element = e.local_name().as_ref().to_vec(),
+            Event::Text(text) => {
+                if let Some((_, _, altitude, time)) = point.as_mut() {
+                    let text = text.unescape().map_err(invalid)?;
+                    match element.as_slice() {
+                        b"ele" => *altitude = text.parse().ok(),
+                        b"time" => *time = parse_time(&text),
+                        _ => (),
+                    }
+                }
+            }
+            Event::End(e) if e.local_name().as_ref() == b"trkpt" => {
+                if let Some((lat, lon, altitude, Some(time))) = point.take() {
+                    track.push(TrackPoint {
+                        time,
+                        lat,
+                        lon,
+                        altitude,
+                    });
+                }
+            }
+            Event::End(_) => element.clear(),
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+    Ok(track)
+}
+
+// Seconds since the unix epoch, given as a plain number or as ISO 8601 UTC time
+// like 2023-06-01T10:15:30.5Z or 2023-06-01 10:15:30+02:00
+pub fn parse_time(value: &str) -> Option<f64> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<f64>() {
+        return Some(seconds);
+    }
+
+    let (date, time) = value.split_once(['T', ' '])?;
+    let mut date_parts = date.split('-');
+    let year: i64 = date_parts.next()?.parse().ok()?;
+    let month: i64 = date_parts.next()?.parse().ok()?;
+    let day: i64 = date_parts.next()?.parse().ok()?;
+
+    // Split off the zone suffix, Z or an offset from UTC
+    let (clock, offset) = if let Some(clock) = time.strip_suffix('Z') {
+        (clock, 0)
+    } else if let Some(index) = time.rfind(['+', '-']) {
+        let (clock, zone) = time.split_at(index);
+        let sign = if zone.starts_with('-') { -1 } else { 1 };
+        let (hours, minutes) = zone[1..].split_once(':').unwrap_or((&zone[1..], "0"));
+        let offset = hours.parse::<i64>().ok()? * 3600 + minutes.parse::<i64>().ok()? * 60;
+        (clock, sign * offset)
+    } else {
+        (time, 0)
+    };
+
+    let mut clock_parts = clock.split(':');
+    let hours: i64 = clock_parts.next()?.parse().ok()?;
+    let minutes: i64 = clock_parts.next()?.parse().ok()?;
+    let seconds: f64 = clock_parts.next().unwrap_or("0").parse().ok()?;
+
+    // Days from the civil date, Howard Hinnant's algorithm
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146097 + day_of_era - 719468;
+
+    Some((days * 86400 + hours * 3600 + minutes * 60 - offset) as f64 + seconds)
+}
@@ -0,0 +1,76 @@
+use crate::error::Result;
+use crate::job::{JobContext, JobStatus, Jobs, RouteRequest};
+use crate::solver::compare::{self, CompareRequest};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::thread;
+use tauri::{AppHandle, Manager, State};
+
+// Calculate the route on its own thread. Progress is emitted as job-progress events,
+// job-finished tells the frontend to fetch the result with get_job_result.
+#[tauri::command]
+pub fn start_route_job(
+    request: RouteRequest,
+    app: AppHandle,
+    jobs: State<'_, Jobs>,
+) -> Result<u64> {
+    let (job_id, cancelled) = jobs.start()?;
+    println!("Received route job {}: {}", job_id, request.algorithm);
+    spawn_job(job_id, cancelled, app, move |context| request.run(context));
+    Ok(job_id)
+}
+
+// Run every solver on the area within the time budget, the same way as a route job
+#[tauri::command]
+pub fn start_compare_job(
+    request: CompareRequest,
+    app: AppHandle,
+    jobs: State<'_, Jobs>,
+) -> Result<u64> {
+    let (job_id, cancelled) = jobs.start()?;
+    println!(
+        "Received compare job {}: {} s budget",
+        job_id, request.budget_seconds
+    );
+    spawn_job(job_id, cancelled, app, move |context| {
+        compare::compare(request, context)
+    });
+    Ok(job_id)
+}
+
+// Run the work on its own thread, storing its outcome under the job id when it is done
+pub(crate) fn spawn_job<T: Into<JobStatus>>(
+    job_id: u64,
+    cancelled: Arc<AtomicBool>,
+    app: AppHandle,
+    work: impl FnOnce(&JobContext) -> Result<T> + Send + 'static,
+) {
+    let progress_app = app.clone();
+    let context = JobContext::new(job_id, cancelled, move |progress| {
+        if let Err(err) = progress_app.emit_all("job-progress", progress) {
+            eprintln!("Failed to emit job progress: {}", err);
+        }
+    });
+
+    thread::spawn(move || {
+        let result = work(&context);
+        if let Err(err) = app.state::<Jobs>().finish(job_id, result) {
+            eprintln!("Failed to store the result of job {}: {}", job_id, err);
+        }
+        if let Err(err) = app.emit_all("job-finished", job_id) {
+            eprintln!("Failed to emit job finished: {}", err);
+        }
+    });
+}
+
+#[tauri::command]
+pub fn cancel_job(job_id: u64, jobs: State<'_, Jobs>) -> Result<()> {
+    println!("Received cancel job: {}", job_id);
+    jobs.cancel(job_id)
+}
+
+// Running while the job is busy, otherwise its outcome, which can be fetched only once
+#[tauri::command]
+pub fn get_job_result(job_id: u64, jobs: State<'_, Jobs>) -> Result<JobStatus> {
+    jobs.take_status(job_id)
+}
@@ -0,0 +1,360 @@
+pub mod job_handle;
+
+use crate::error::{Error, Result};
+use crate::solver::compare::{Comparison, SolverComparison};
+use crate::solver::{self, SolverInput, SolverOutput};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// Smallest progress step in percent worth an event, keeps the frontend from being flooded
+const PROGRESS_STEP: f64 = 1.0;
+
+// How long the outcome of a job is kept when the frontend never fetches it
+const FINISHED_JOB_LIFETIME: Duration = Duration::from_secs(10 * 60);
+
+// Progress of a running job, sent to the frontend as the job-progress event
+#[derive(Debug, Clone, Serialize)]
+pub struct JobProgress {
+    pub job_id: u64,              // id of the job
+    pub best_length: Option<f64>, // length of the shortest route found so far
+    pub percent: f64,             // share of the search explored, 0 to 100
+}
+
+// Handed to the algorithms, they poll it for cancellation and report progress through it.
+// Clones share the same job, so worker threads can take their own.
+#[derive(Clone)]
+pub struct JobContext {
+    job_id: u64,
+    cancelled: Arc<AtomicBool>,
+    reporter: Option<Arc<dyn Fn(JobProgress) + Send + Sync>>,
+    last_progress: Arc<Mutex<(f64, Option<f64>)>>, // last reported percent and best length
+}
+
+impl JobContext {
+    pub fn new(
+        job_id: u64,
+        cancelled: Arc<AtomicBool>,
+        reporter: impl Fn(JobProgress) + Send + Sync + 'static,
+    ) -> JobContext {
+        JobContext {
+            job_id,
+            cancelled,
+            reporter: Some(Arc::new(reporter)),
+            last_progress: Arc::new(Mutex::new((-PROGRESS_STEP, None))),
+        }
+    }
+
+    // Context of a direct call, never cancelled and reporting nowhere
+    pub fn detached() -> JobContext {
+        JobContext::stoppable(Arc::new(AtomicBool::new(false)))
+    }
+
+    // Context of a direct call that is stopped through the flag, reporting nowhere
+    pub fn stoppable(cancelled: Arc<AtomicBool>) -> JobContext {
+        JobContext {
+            job_id: 0,
+            cancelled,
+            reporter: None,
+            last_progress: Arc::new(Mutex::new((-PROGRESS_STEP, None))),
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    pub fn check_cancelled(&self) -> Result<()> {
+        if self.is_cancelled() {
+            return Err(Error::cancelled(format!(
+                "Job {} was cancelled.",
+                self.job_id
+            )));
+        }
+        Ok(())
+    }
+
+    // Report only when the search moved on by a step or found a shorter route
+    pub fn report(&self, best_length: Option<f64>, percent: f64) {
+        let reporter = match &self.reporter {
+            Some(reporter) => reporter,
+            None => return,
+        };
+        let mut last_progress = match self.last_progress.lock() {
+            Ok(last_progress) => last_progress,
+            Err(_) => return,
+        };
+
+        let (last_percent, last_best) = *last_progress;
+        let shorter = match (best_length, last_best) {
+            (Some(best), Some(last)) => best < last,
+            (Some(_), None) => true,
+            _ => false,
+        };
+        if !shorter && percent < last_percent + PROGRESS_STEP && percent < 100.0 {
+            return;
+        }
+
+        *last_progress = (percent, best_length.or(last_best));
+        reporter(JobProgress {
+            job_id: self.job_id,
+            best_length: best_length.or(last_best),
+            percent,
+        });
+    }
+}
+
+// Route calculation started as a job, the solver name and its input
+#[derive(Debug, Clone, Deserialize)]
+pub struct RouteRequest {
+    pub algorithm: String, // name of the solver, as listed by list_solvers
+    #[serde(flatten)]
+    pub input: SolverInput, // discretized regions, start point and options
+}
+
+impl RouteRequest {
+    pub fn run(self, context: &JobContext) -> Result<SolverOutput> {
+        solver::solve(&self.algorithm, &self.input, context)
+    }
+}
+
+// State of a job as the frontend fetches it
+#[derive(Debug, Serialize)]
+#[serde(tag = "state")]
+pub enum JobStatus {
+    Running,
+    Finished {
+        waypoints: Vec<(f64, f64)>,        // calculated route
+        route_length: f64,                 // route length in meters
+        metadata: BTreeMap<String, Value>, // solver specific facts about the route
+    },
+    Compared {
+        results: Vec<SolverComparison>, // one row per solver
+        best: Option<String>,           // feasible solver with the shortest mission, if any
+    },
+    Planned {
+        mission_id: u64,     // mission saved with the new route, fetched with get_mission
+        ground_speed: f64,   // speed the route is flown at
+        speed_reduced: bool, // the camera keeps the uav below its flight speed
+    },
+    Cancelled,
+    Failed {
+        error: Error, // why the calculation failed
+    },
+}
+
+impl From<SolverOutput> for JobStatus {
+    fn from(output: SolverOutput) -> JobStatus {
+        JobStatus::Finished {
+            waypoints: output.waypoints,
+            route_length: output.route_length,
+            metadata: output.metadata,
+        }
+    }
+}
+
+impl From<Comparison> for JobStatus {
+    fn from(comparison: Comparison) -> JobStatus {
+        JobStatus::Compared {
+            results: comparison.results,
+            best: comparison.best,
+        }
+    }
+}
+
+struct Job {
+    cancelled: Arc<AtomicBool>,
+    status: JobStatus,
+    finished: Option<Instant>, // when the job was done, None while it runs
+}
+
+// Jobs started and not fetched yet, managed by tauri next to the database
+#[derive(Default)]
+pub struct Jobs {
+    next_id: AtomicU64,
+    jobs: Mutex<HashMap<u64, Job>>,
+}
+
+impl Jobs {
+    // Register a running job, returns its id and the flag cancelling it.
+    // Outcomes nobody fetched in time are dropped on the way.
+    pub fn start(&self) -> Result<(u64, Arc<AtomicBool>)> {
+        self.forget_finished(FINISHED_JOB_LIFETIME)?;
+        let job_id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let cancelled = Arc::new(AtomicBool::new(false));
+        self.lock()?.insert(
+            job_id,
+            Job {
+                cancelled: Arc::clone(&cancelled),
+                status: JobStatus::Running,
+                finished: None,
+            },
+        );
+        Ok((job_id, cancelled))
+    }
+
+    pub fn finish(&self, job_id: u64, result: Result<impl Into<JobStatus>>) -> Result<()> {
+        let status = match result {
+            Ok(outcome) => outcome.into(),
+            Err(Error::Cancelled(_)) => JobStatus::Cancelled,
+            Err(error) => JobStatus::Failed { error },
+        };
+        if let Some(job) = self.lock()?.get_mut(&job_id) {
+            job.status = status;
+            job.finished = Some(Instant::now());
+        }
+        Ok(())
+    }
+
+    // Ask a running job to stop, it finishes as cancelled at its next check
+    pub fn cancel(&self, job_id: u64) -> Result<()> {
+        let jobs = self.lock()?;
+        let job = jobs
+            .get(&job_id)
+            .ok_or_else(|| Error::validation(format!("Job {} does not exist.", job_id)))?;
+        job.cancelled.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    // Status of the job, a job that is done is forgotten once fetched
+    pub fn take_status(&self, job_id: u64) -> Result<JobStatus> {
+        let mut jobs = self.lock()?;
+        match jobs.remove(&job_id) {
+            None => Err(Error::validation(format!("Job {} does not exist.", job_id))),
+            Some(job) if matches!(job.status, JobStatus::Running) => {
+                jobs.insert(job_id, job);
+                Ok(JobStatus::Running)
+            }
+            Some(job) => Ok(job.status),
+        }
+    }
+
+    // Drop the jobs done for longer than the lifetime, running ones are kept
+    fn forget_finished(&self, lifetime: Duration) -> Result<()> {
+        self.lock()?.retain(|_, job| {
+            job.finished
+                .map_or(true, |finished| finished.elapsed() < lifetime)
+        });
+        Ok(())
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, HashMap<u64, Job>>> {
+        self.jobs
+            .lock()
+            .map_err(|_| Error::algorithm("The job registry lock is poisoned."))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recording_context() -> (JobContext, Arc<Mutex<Vec<JobProgress>>>) {
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let recorder = Arc::clone(&reported);
+        let context = JobContext::new(1, Arc::new(AtomicBool::new(false)), move |progress| {
+            recorder.lock().unwrap().push(progress)
+        });
+        (context, reported)
+    }
+
+    fn output() -> Result<SolverOutput> {
+        SolverOutput::new(vec![(0.0, 0.0), (3.0, 4.0), (0.0, 0.0)])
+    }
+
+    #[test]
+    fn progress_is_reported_by_step_or_shorter_route() {
+        let (context, reported) = recording_context();
+        context.report(None, 0.0);
+        context.report(None, 0.5);
+        context.report(None, 1.0);
+        context.report(Some(10.0), 1.2);
+        context.report(Some(12.0), 1.4);
+        context.report(Some(8.0), 1.6);
+        context.report(None, 2.0);
+        context.report(None, 100.0);
+
+        let reported: Vec<(Option<f64>, f64)> = reported
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|progress| (progress.best_length, progress.percent))
+            .collect();
+        assert_eq!(
+            reported,
+            vec![
+                (None, 0.0),
+                (None, 1.0),
+                (Some(10.0), 1.2),
+                (Some(8.0), 1.6),
+                (Some(8.0), 100.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn finished_job_is_fetched_once() {
+        let jobs = Jobs::default();
+        let (job_id, _) = jobs.start().unwrap();
+        assert!(matches!(jobs.take_status(job_id), Ok(JobStatus::Running)));
+        assert!(matches!(jobs.take_status(job_id), Ok(JobStatus::Running)));
+
+        jobs.finish(job_id, output()).unwrap();
+        assert!(matches!(
+            jobs.take_status(job_id),
+            Ok(JobStatus::Finished { route_length, .. }) if route_length == 10.0
+        ));
+        assert!(matches!(
+            jobs.take_status(job_id),
+            Err(Error::Validation(_))
+        ));
+        assert!(matches!(jobs.take_status(42), Err(Error::Validation(_))));
+    }
+
+    #[test]
+    fn cancelled_job_finishes_as_cancelled() {
+        let jobs = Jobs::default();
+        let (job_id, cancelled) = jobs.start().unwrap();
+        jobs.cancel(job_id).unwrap();
+        let context = JobContext::stoppable(cancelled);
+        assert!(context.is_cancelled());
+
+        let result: Result<SolverOutput> = context.check_cancelled().and_then(|_| output());
+        jobs.finish(job_id, result).unwrap();
+        assert!(matches!(jobs.take_status(job_id), Ok(JobStatus::Cancelled)));
+
+        let (job_id, _) = jobs.start().unwrap();
+        let failed: Result<SolverOutput> = Err(Error::algorithm("No route."));
+        jobs.finish(job_id, failed).unwrap();
+        assert!(matches!(
+            jobs.take_status(job_id),
+            Ok(JobStatus::Failed {
+                error: Error::Algorithm(_)
+            })
+        ));
+        assert!(matches!(jobs.cancel(job_id), Err(Error::Validation(_))));
+    }
+
+    #[test]
+    fn unfetched_jobs_are_forgotten() {
+        let jobs = Jobs::default();
+        let (finished_id, _) = jobs.start().unwrap();
+        let (running_id, _) = jobs.start().unwrap();
+        jobs.finish(finished_id, output()).unwrap();
+
+        jobs.forget_finished(FINISHED_JOB_LIFETIME).unwrap();
+        assert_eq!(jobs.lock().unwrap().len(), 2);
+        jobs.forget_finished(Duration::ZERO).unwrap();
+        assert!(matches!(
+            jobs.take_status(finished_id),
+            Err(Error::Validation(_))
+        ));
+        assert!(matches!(
+            jobs.take_status(running_id),
+            Ok(JobStatus::Running)
+        ));
+    }
+}
@@ -3,25 +3,57 @@
     windows_subsystem = "windows"
 )]
 
-use rusqlite::Connection;
+use tauri::Manager;
 mod algorithms;
+mod area_import;
 
 mod camera;
+#[cfg(test)]
+mod corpus;
+mod csv_table;
+mod database;
+mod error;
+mod export;
+mod flight_log;
+mod job;
+mod migrations;
+mod mission;
+mod payload;
+mod projection;
+mod solver;
+mod spatial_index;
+mod trigger;
 mod uav;
 
 use uav::uav_handle;
 
 use camera::camera_handle;
 
-fn main() {
-    {
-        let conn = Connection::open("mydatabase.db").expect("Cant open base");
+use payload::payload_handle;
+
+use mission::mission_handle;
+
+use flight_log::flight_log_handle;
+
+use export::export_handle;
 
-        uav::uav_sql::create_table(&conn).expect("cant create uav table");
-        camera::camera_sql::create_table(&conn).expect("Cant create camera table");
-    }
+use area_import::area_import_handle;
 
+use job::job_handle;
+
+use solver::solver_handle;
+
+use database::Database;
+use job::Jobs;
+
+fn main() {
     tauri::Builder::default()
+        .setup(|app| {
+            let path = database::database_path(&app.path_resolver())?;
+            app.manage(Database::open(&path)?);
+            app.manage(Jobs::default());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             uav_handle::new_uav,
             uav_handle::update_uav,
@@ -31,12 +63,35 @@ fn main() {
             camera_handle::update_camera,
             camera_handle::delete_camera,
             camera_handle::get_all_cameras_vec,
+            camera_handle::capture_speed_limit,
+            payload_handle::new_payload,
+            payload_handle::update_payload,
+            payload_handle::delete_payload,
+            payload_handle::get_all_payloads_vec,
+            payload_handle::get_valid_payloads_vec,
+            mission_handle::new_mission,
+            mission_handle::update_mission,
+            mission_handle::delete_mission,
+            mission_handle::get_mission,
+            mission_handle::get_all_missions_vec,
+            mission_handle::start_plan_job,
+            mission_handle::get_mission_revisions,
+            mission_handle::diff_mission_revisions,
+            flight_log_handle::import_flight_log,
+            export_handle::export_route,
+            area_import_handle::import_area,
+            job_handle::start_route_job,
+            job_handle::start_compare_job,
+            job_handle::cancel_job,
+            job_handle::get_job_result,
+            solver_handle::list_solvers,
             algorithms::discretize_area,
-            algorithms::nearest_neighbor,
-            algorithms::brute_force,
-            algorithms::rectangular_areas,
+            algorithms::discretize_regions,
+            algorithms::discretize_corridor,
             algorithms::calculate_distance,
             algorithms::search_long_distance,
+            trigger::camera_trigger_plan,
+            trigger::region_trigger_plan,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
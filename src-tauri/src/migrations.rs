@@ -0,0 +1,106 @@
+use crate::camera::camera_sql;
+use crate::error::{Error, Result};
+use crate::mission::mission_sql;
+use crate::payload::payload_sql;
+use crate::uav::uav_sql;
+use rusqlite::Connection;
+
+// Schema change that brings the database to the given version
+struct Migration {
+    version: u32,
+    description: &'static str,
+    apply: fn(&Connection) -> rusqlite::Result<()>,
+}
+
+// Numbered migrations, append only. Never edit a migration that has been released,
+// add a new one instead.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "create camera and uav tables",
+        apply: |conn| {
+            camera_sql::create_table(conn)?;
+            uav_sql::create_table(conn)?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 2,
+        description: "move cameras into payload configurations",
+        apply: |conn| {
+            conn.execute("DROP INDEX IF EXISTS camera_id_index", ())?;
+            payload_sql::create_table(conn)?;
+            payload_sql::copy_uav_cameras(conn)?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 3,
+        description: "create mission tables",
+        apply: |conn| {
+            mission_sql::create_table(conn)?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 4,
+        description: "create mission revision tables",
+        apply: mission_sql::create_revision_table,
+    },
+    Migration {
+        version: 5,
+        description: "add camera shutter interval and exposure time",
+        apply: camera_sql::add_timing_columns,
+    },
+    Migration {
+        version: 6,
+        description: "create mission region settings table",
+        apply: mission_sql::create_region_table,
+    },
+    Migration {
+        version: 7,
+        description: "add mission payload configuration",
+        apply: mission_sql::add_payload_column,
+    },
+    Migration {
+        version: 8,
+        description: "add mission max blur",
+        apply: mission_sql::add_max_blur_column,
+    },
+];
+
+// Latest schema version this binary understands
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map_or(0, |migration| migration.version)
+}
+
+pub fn schema_version(conn: &Connection) -> Result<u32> {
+    Ok(conn.query_row("PRAGMA user_version", [], |row| row.get(0))?)
+}
+
+// Apply every migration newer than the stored schema version, each in its own transaction.
+// Databases written by a newer binary are refused instead of being modified.
+pub fn migrate(conn: &mut Connection) -> Result<u32> {
+    let current = schema_version(conn)?;
+    let latest = latest_version();
+
+    if current > latest {
+        return Err(Error::database(format!(
+            "Database schema version {} is newer than the supported version {}, update the application.",
+            current, latest
+        )));
+    }
+
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        println!(
+            "Applying migration {}: {}",
+            migration.version, migration.description
+        );
+        let tx = conn.transaction()?;
+        (migration.apply)(&tx)?;
+        tx.pragma_update(None, "user_version", migration.version)?;
+        tx.commit()?;
+    }
+
+    Ok(latest)
+}
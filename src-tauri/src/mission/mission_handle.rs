@@ -0,0 +1,214 @@
+use crate::camera::{camera_sql, Camera};
+use crate::database::Database;
+use crate::error::{Error, Result};
+use crate::job::{job_handle, JobStatus, Jobs};
+use crate::mission::mission_sql;
+use crate::mission::revision::{self, MissionRevision, RevisionDiff};
+use crate::mission::Mission;
+use crate::payload::{payload_sql, Payload};
+use crate::uav::{uav_sql, Uav};
+use rusqlite::Connection;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use tauri::{AppHandle, Manager, State};
+
+#[tauri::command]
+pub fn new_mission(mission: Mission, database: State<'_, Database>) -> Result<u64> {
+    let mut conn = database.connection()?;
+    println!("Received new mission: {}", mission.name);
+    let tx = conn.transaction()?;
+    let mission_id = mission_sql::insert(&mission, &tx)?;
+    tx.commit()?;
+    Ok(mission_id)
+}
+
+#[tauri::command]
+pub fn update_mission(mission: Mission, database: State<'_, Database>) -> Result<()> {
+    let mut conn = database.connection()?;
+    println!("Received updated mission: {}", mission.name);
+    let tx = conn.transaction()?;
+    mission_sql::update(&mission, &tx)?;
+    tx.commit()?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn delete_mission(mission: Mission, database: State<'_, Database>) -> Result<()> {
+    let conn = database.connection()?;
+    println!("Received delete mission: {}", mission.name);
+    mission_sql::delete(&mission, &conn)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_mission(mission_id: u64, database: State<'_, Database>) -> Result<Mission> {
+    let conn = database.connection()?;
+    mission_sql::get_mission(mission_id, &conn)?
+        .ok_or_else(|| Error::validation(format!("Mission {} does not exist.", mission_id)))
+}
+
+#[tauri::command]
+pub fn get_all_missions_vec(database: State<'_, Database>) -> Result<Vec<Mission>> {
+    let conn = database.connection()?;
+    Ok(mission_sql::get_missions_vec(&conn)?)
+}
+
+// Re-plan a saved mission with its stored parameters as a job, the same way as a route job.
+// The database is not locked while the route is calculated, the new route and its revision
+// are saved when the job finishes.
+#[tauri::command]
+pub fn start_plan_job(
+    mission_id: u64,
+    app: AppHandle,
+    jobs: State<'_, Jobs>,
+    database: State<'_, Database>,
+) -> Result<u64> {
+    let (mut mission, uav, camera, region_cameras, payload) = {
+        let conn = database.connection()?;
+        let mission = mission_sql::get_mission(mission_id, &conn)?
+            .ok_or_else(|| Error::validation(format!("Mission {} does not exist.", mission_id)))?;
+        let (uav, camera, region_cameras) = mission_equipment(&mission, &conn)?;
+        let payload = mission_payload(&mission, &uav, &region_cameras, &conn)?;
+        (mission, uav, camera, region_cameras, payload)
+    };
+
+    let (job_id, cancelled) = jobs.start()?;
+    println!("Received plan job {}: {}", job_id, mission.name);
+    let database_app = app.clone();
+    job_handle::spawn_job(job_id, cancelled, app, move |context| {
+        let profile = mission.plan(&uav, &camera, &region_cameras, context)?;
+        mission.payload_id = Some(payload.id());
+        // A job cancelled after the route was found leaves the mission as it was
+        context.check_cancelled()?;
+        save_plan(&mission, &uav, &database_app.state::<Database>())?;
+        Ok(JobStatus::Planned {
+            mission_id,
+            ground_speed: profile.ground_speed,
+            speed_reduced: profile.speed_reduced,
+        })
+    });
+    Ok(job_id)
+}
+
+// Every plan is kept as a revision next to the current state of the mission
+fn save_plan(mission: &Mission, uav: &Uav, database: &Database) -> Result<()> {
+    let mut conn = database.connection()?;
+    let tx = conn.transaction()?;
+    if mission_sql::update(mission, &tx)? == 0 {
+        return Err(Error::validation(format!(
+            "Mission {} was deleted while planning.",
+            mission.id
+        )));
+    }
+    mission_sql::insert_revision(&MissionRevision::new(mission, uav), &tx)?;
+    tx.commit()?;
+    Ok(())
+}
+
+// Uav and camera selected for the mission, with the cameras of polygons that use another one
+pub(crate) fn mission_equipment(
+    mission: &Mission,
+    conn: &Connection,
+) -> Result<(Uav, Camera, HashMap<u64, Camera>)> {
+    let uav_id = mission
+        .uav_id
+        .ok_or_else(|| Error::validation("Mission has no UAV selected."))?;
+    let uav = uav_sql::get_uav(uav_id, conn)?
+        .ok_or_else(|| Error::validation(format!("UAV {} does not exist.", uav_id)))?;
+    let camera_id = mission
+        .camera_id
+        .ok_or_else(|| Error::validation("Mission has no camera selected."))?;
+    let camera = camera_sql::get_camera(camera_id, conn)?
+        .ok_or_else(|| Error::validation(format!("Camera {} does not exist.", camera_id)))?;
+
+    let mut region_cameras = HashMap::new();
+    for camera_id in mission.regions.iter().filter_map(|region| region.camera_id) {
+        if let Entry::Vacant(entry) = region_cameras.entry(camera_id) {
+            let camera = camera_sql::get_camera(camera_id, conn)?.ok_or_else(|| {
+                Error::validation(format!("Camera {} does not exist.", camera_id))
+            })?;
+            entry.insert(camera);
+        }
+    }
+    Ok((uav, camera, region_cameras))
+}
+
+// Valid payload configuration of the uav carrying every camera the mission uses.
+// Without a configuration on the mission the planner picks the first one that does.
+pub(crate) fn mission_payload(
+    mission: &Mission,
+    uav: &Uav,
+    region_cameras: &HashMap<u64, Camera>,
+    conn: &Connection,
+) -> Result<Payload> {
+    let camera_ids: Vec<u64> = mission
+        .camera_id
+        .iter()
+        .chain(region_cameras.keys())
+        .copied()
+        .collect();
+    let carries_cameras = |payload: &Payload| {
+        camera_ids
+            .iter()
+            .all(|camera_id| payload.camera_ids.contains(camera_id))
+    };
+
+    let payloads = payload_sql::get_payloads_vec(mission.uav_id, true, conn)?;
+    match mission.payload_id {
+        Some(payload_id) => {
+            let payload = payloads
+                .into_iter()
+                .find(|payload| payload.id() == payload_id)
+                .ok_or_else(|| {
+                    Error::validation(format!(
+                        "Payload configuration {} is not a valid configuration of UAV \"{}\".",
+                        payload_id, uav.name
+                    ))
+                })?;
+            if !carries_cameras(&payload) {
+                return Err(Error::validation(format!(
+                    "Payload configuration \"{}\" does not carry every camera of the mission.",
+                    payload.name
+                )));
+            }
+            Ok(payload)
+        }
+        None => payloads.into_iter().find(carries_cameras).ok_or_else(|| {
+            Error::validation(format!(
+                "No valid payload configuration of UAV \"{}\" carries every camera of the mission.",
+                uav.name
+            ))
+        }),
+    }
+}
+
+#[tauri::command]
+pub fn get_mission_revisions(
+    mission_id: u64,
+    database: State<'_, Database>,
+) -> Result<Vec<MissionRevision>> {
+    let conn = database.connection()?;
+    Ok(mission_sql::get_revisions_vec(mission_id, &conn)?)
+}
+
+// Compare two revisions of one mission, from_revision_id is treated as the older one
+#[tauri::command]
+pub fn diff_mission_revisions(
+    from_revision_id: u64,
+    to_revision_id: u64,
+    database: State<'_, Database>,
+) -> Result<RevisionDiff> {
+    let conn = database.connection()?;
+    let from = mission_sql::get_revision(from_revision_id, &conn)?.ok_or_else(|| {
+        Error::validation(format!("Revision {} does not exist.", from_revision_id))
+    })?;
+    let to = mission_sql::get_revision(to_revision_id, &conn)?
+        .ok_or_else(|| Error::validation(format!("Revision {} does not exist.", to_revision_id)))?;
+    if from.mission_id != to.mission_id {
+        return Err(Error::validation(format!(
+            "Revisions {} and {} belong to different missions.",
+            from_revision_id, to_revision_id
+        )));
+    }
+    Ok(revision::diff(&from, &to))
+}
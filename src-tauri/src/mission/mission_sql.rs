@@ -0,0 +1,583 @@
+use crate::mission::revision::{MissionRevision, RevisionParameters};
+use crate::mission::{Mission, RegionSettings};
+use rusqlite::types::Type;
+use rusqlite::{Connection, Error, OptionalExtension, Result, Row};
+
+pub fn create_table(conn: &Connection) -> Result<usize> {
+    let mission_create = conn.execute(
+        "CREATE TABLE IF NOT EXISTS mission (
+                mission_id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+                mission_name TEXT NOT NULL,
+                mission_projection TEXT NOT NULL,
+                mission_start_x REAL NOT NULL,
+                mission_start_y REAL NOT NULL,
+                mission_altitude REAL NOT NULL CHECK (mission_altitude >= 0),
+                mission_overlap REAL NOT NULL CHECK (
+                    mission_overlap >= 0
+                    AND mission_overlap < 100
+                ),
+                mission_direction REAL NOT NULL,
+                mission_algorithm TEXT NOT NULL,
+                mission_route_length REAL DEFAULT 0 NOT NULL CHECK (mission_route_length >= 0),
+                mission_duration REAL DEFAULT 0 NOT NULL CHECK (mission_duration >= 0),
+                mission_photo_count INTEGER DEFAULT 0 NOT NULL CHECK (mission_photo_count >= 0),
+                uav_id INTEGER,
+                camera_id INTEGER,
+                    FOREIGN KEY (uav_id)
+                    REFERENCES uav (uav_id)
+                    ON DELETE SET NULL,
+                    FOREIGN KEY (camera_id)
+                    REFERENCES camera (camera_id)
+                    ON DELETE SET NULL
+                )",
+        (),
+    );
+
+    let area_create = conn.execute(
+        "CREATE TABLE IF NOT EXISTS mission_area_point (
+                mission_id INTEGER NOT NULL,
+                polygon_index INTEGER NOT NULL,
+                point_index INTEGER NOT NULL,
+                point_x REAL NOT NULL,
+                point_y REAL NOT NULL,
+                    PRIMARY KEY (mission_id, polygon_index, point_index),
+                    FOREIGN KEY (mission_id)
+                    REFERENCES mission (mission_id)
+                    ON DELETE CASCADE
+                )",
+        (),
+    );
+
+    let waypoint_create = conn.execute(
+        "CREATE TABLE IF NOT EXISTS mission_waypoint (
+                mission_id INTEGER NOT NULL,
+                waypoint_index INTEGER NOT NULL,
+                waypoint_x REAL NOT NULL,
+                waypoint_y REAL NOT NULL,
+                    PRIMARY KEY (mission_id, waypoint_index),
+                    FOREIGN KEY (mission_id)
+                    REFERENCES mission (mission_id)
+                    ON DELETE CASCADE
+                )",
+        (),
+    );
+
+    match (mission_create, area_create, waypoint_create) {
+        (Ok(val1), Ok(val2), Ok(val3)) => Ok(val1 + val2 + val3),
+        (Err(err), _, _) => Err(err),
+        (_, Err(err), _) => Err(err),
+        (_, _, Err(err)) => Err(err),
+    }
+}
+
+// Payload configuration the mission is flown with, picked by the planner when unset
+pub fn add_payload_column(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE mission ADD COLUMN payload_id INTEGER
+            REFERENCES payload (payload_id) ON DELETE SET NULL;",
+    )
+}
+
+// Share of the GSD a photo may smear, the camera default when NULL
+pub fn add_max_blur_column(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE mission ADD COLUMN mission_max_blur REAL CHECK (mission_max_blur > 0);",
+    )
+}
+
+// Settings of single polygons, a row only exists for polygons with an override
+pub fn create_region_table(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS mission_region (
+                mission_id INTEGER NOT NULL,
+                polygon_index INTEGER NOT NULL,
+                region_direction REAL,
+                region_altitude REAL CHECK (region_altitude >= 0),
+                region_overlap REAL CHECK (
+                    region_overlap >= 0
+                    AND region_overlap < 100
+                ),
+                camera_id INTEGER,
+                PRIMARY KEY (mission_id, polygon_index),
+                FOREIGN KEY (mission_id)
+                    REFERENCES mission (mission_id)
+                    ON DELETE CASCADE,
+                FOREIGN KEY (camera_id)
+                    REFERENCES camera (camera_id)
+                    ON DELETE SET NULL
+                )",
+    )
+}
+
+// Replace the polygons, region settings and waypoints of the mission
+fn insert_points(mission_id: u64, mission: &Mission, conn: &Connection) -> Result<()> {
+    conn.execute(
+        "DELETE FROM mission_area_point
+        WHERE mission_id = ?1",
+        (&mission_id,),
+    )?;
+    conn.execute(
+        "DELETE FROM mission_region
+        WHERE mission_id = ?1",
+        (&mission_id,),
+    )?;
+    conn.execute(
+        "DELETE FROM mission_waypoint
+        WHERE mission_id = ?1",
+        (&mission_id,),
+    )?;
+
+    let mut stmt = conn.prepare(
+        "INSERT INTO mission_area_point (
+                mission_id,
+                polygon_index,
+                point_index,
+                point_x,
+                point_y
+            ) VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+    for (polygon_index, polygon) in mission.polygons.iter().enumerate() {
+        for (point_index, (x, y)) in polygon.iter().enumerate() {
+            stmt.execute((&mission_id, polygon_index, point_index, x, y))?;
+        }
+    }
+
+    let mut stmt = conn.prepare(
+        "INSERT INTO mission_region (
+                mission_id,
+                polygon_index,
+                region_direction,
+                region_altitude,
+                region_overlap,
+                camera_id
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    )?;
+    for (polygon_index, region) in mission.regions.iter().enumerate() {
+        if *region != RegionSettings::default() {
+            stmt.execute((
+                &mission_id,
+                polygon_index,
+                &region.direction,
+                &region.altitude,
+                &region.overlap,
+                &region.camera_id,
+            ))?;
+        }
+    }
+
+    let mut stmt = conn.prepare(
+        "INSERT INTO mission_waypoint (
+                mission_id,
+                waypoint_index,
+                waypoint_x,
+                waypoint_y
+            ) VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    for (waypoint_index, (x, y)) in mission.waypoints.iter().enumerate() {
+        stmt.execute((&mission_id, waypoint_index, x, y))?;
+    }
+    Ok(())
+}
+
+// Should run inside a transaction, the polygons and waypoints are separate rows
+pub fn insert(mission: &Mission, conn: &Connection) -> Result<u64> {
+    conn.execute(
+        "INSERT INTO mission (
+                mission_name,
+                mission_projection,
+                mission_start_x,
+                mission_start_y,
+                mission_altitude,
+                mission_overlap,
+                mission_direction,
+                mission_algorithm,
+                mission_route_length,
+                mission_duration,
+                mission_photo_count,
+                uav_id,
+                camera_id,
+                payload_id,
+                mission_max_blur
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+        (
+            &mission.name,
+            &mission.projection,
+            &mission.start_point.0,
+            &mission.start_point.1,
+            &mission.altitude,
+            &mission.overlap,
+            &mission.direction,
+            &mission.algorithm,
+            &mission.route_length,
+            &mission.duration,
+            &mission.photo_count,
+            &mission.uav_id,
+            &mission.camera_id,
+            &mission.payload_id,
+            &mission.max_blur,
+        ),
+    )?;
+
+    let mission_id = conn.last_insert_rowid() as u64;
+    insert_points(mission_id, mission, conn)?;
+    Ok(mission_id)
+}
+
+// Should run inside a transaction, the polygons and waypoints are separate rows
+pub fn update(mission: &Mission, conn: &Connection) -> Result<usize> {
+    let updated = conn.execute(
+        "
+                UPDATE mission SET
+                    mission_name = ?1,
+                    mission_projection = ?2,
+                    mission_start_x = ?3,
+                    mission_start_y = ?4,
+                    mission_altitude = ?5,
+                    mission_overlap = ?6,
+                    mission_direction = ?7,
+                    mission_algorithm = ?8,
+                    mission_route_length = ?9,
+                    mission_duration = ?10,
+                    mission_photo_count = ?11,
+                    uav_id = ?12,
+                    camera_id = ?13,
+                    payload_id = ?14,
+                    mission_max_blur = ?15
+                WHERE mission_id = ?16",
+        (
+            &mission.name,
+            &mission.projection,
+            &mission.start_point.0,
+            &mission.start_point.1,
+            &mission.altitude,
+            &mission.overlap,
+            &mission.direction,
+            &mission.algorithm,
+            &mission.route_length,
+            &mission.duration,
+            &mission.photo_count,
+            &mission.uav_id,
+            &mission.camera_id,
+            &mission.payload_id,
+            &mission.max_blur,
+            &mission.id,
+        ),
+    )?;
+
+    if updated > 0 {
+        insert_points(mission.id, mission, conn)?;
+    }
+    Ok(updated)
+}
+
+pub fn delete(mission: &Mission, conn: &Connection) -> Result<usize> {
+    conn.execute(
+        "DELETE FROM mission 
+        WHERE mission_id = ?1",
+        (&mission.id,),
+    )
+}
+
+const SELECT_MISSION: &str = "SELECT
+                mission_id,
+                mission_name,
+                mission_projection,
+                mission_start_x,
+                mission_start_y,
+                mission_altitude,
+                mission_overlap,
+                mission_direction,
+                mission_algorithm,
+                uav_id,
+                camera_id,
+                mission_route_length,
+                mission_duration,
+                mission_photo_count,
+                payload_id,
+                mission_max_blur
+            FROM mission";
+
+// Polygons, region settings and waypoints are loaded separately by load_points
+fn mission_from_row(row: &Row) -> Result<Mission> {
+    Ok(Mission {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        projection: row.get(2)?,
+        polygons: Vec::new(),
+        start_point: (row.get(3)?, row.get(4)?),
+        altitude: row.get(5)?,
+        overlap: row.get(6)?,
+        direction: row.get(7)?,
+        max_blur: row.get(15)?,
+        algorithm: row.get(8)?,
+        uav_id: row.get(9)?,
+        camera_id: row.get(10)?,
+        payload_id: row.get(14)?,
+        regions: Vec::new(),
+        waypoints: Vec::new(),
+        route_length: row.get(11)?,
+        duration: row.get(12)?,
+        photo_count: row.get(13)?,
+    })
+}
+
+fn load_points(mission: &mut Mission, conn: &Connection) -> Result<()> {
+    let mut stmt = conn.prepare(
+        "SELECT polygon_index, point_x, point_y
+            FROM mission_area_point
+            WHERE mission_id = ?1
+            ORDER BY polygon_index, point_index",
+    )?;
+    let points = stmt.query_map((&mission.id,), |row| {
+        Ok((row.get::<_, usize>(0)?, (row.get(1)?, row.get(2)?)))
+    })?;
+    mission.polygons = Vec::new();
+    for point in points {
+        let (polygon_index, point) = point?;
+        while mission.polygons.len() <= polygon_index {
+            mission.polygons.push(Vec::new());
+        }
+        mission.polygons[polygon_index].push(point);
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT polygon_index, region_direction, region_altitude, region_overlap, camera_id
+            FROM mission_region
+            WHERE mission_id = ?1
+            ORDER BY polygon_index",
+    )?;
+    let regions = stmt.query_map((&mission.id,), |row| {
+        Ok((
+            row.get::<_, usize>(0)?,
+            RegionSettings {
+                direction: row.get(1)?,
+                altitude: row.get(2)?,
+                overlap: row.get(3)?,
+                camera_id: row.get(4)?,
+            },
+        ))
+    })?;
+    mission.regions = Vec::new();
+    for region in regions {
+        let (polygon_index, region) = region?;
+        while mission.regions.len() <= polygon_index {
+            mission.regions.push(RegionSettings::default());
+        }
+        mission.regions[polygon_index] = region;
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT waypoint_x, waypoint_y
+            FROM mission_waypoint
+            WHERE mission_id = ?1
+            ORDER BY waypoint_index",
+    )?;
+    mission.waypoints = stmt
+        .query_map((&mission.id,), |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<Vec<(f64, f64)>>>()?;
+    Ok(())
+}
+
+pub fn get_mission(mission_id: u64, conn: &Connection) -> Result<Option<Mission>> {
+    let mission = conn
+        .query_row(
+            &format!("{} WHERE mission_id = ?1", SELECT_MISSION),
+            (&mission_id,),
+            mission_from_row,
+        )
+        .optional()?;
+
+    match mission {
+        Some(mut mission) => {
+            load_points(&mut mission, conn)?;
+            Ok(Some(mission))
+        }
+        None => Ok(None),
+    }
+}
+
+pub fn get_missions(conn: &Connection) -> Result<Vec<Result<Mission>>> {
+    let mut stmt = conn.prepare(SELECT_MISSION)?;
+
+    let mission_iter = stmt.query_map([], |row| {
+        let mut mission = mission_from_row(row)?;
+        load_points(&mut mission, conn)?;
+        Ok(mission)
+    })?;
+
+    Ok(mission_iter.collect())
+}
+
+pub fn get_missions_vec(conn: &Connection) -> Result<Vec<Mission>> {
+    let mission_results = get_missions(conn)?;
+
+    let mut missions: Vec<Mission> = Vec::new();
+    for mission_result in mission_results {
+        match mission_result {
+            Ok(mission) => missions.push(mission),
+            Err(err) => eprintln!("Error processing a Mission: {}", err),
+        }
+    }
+    Ok(missions)
+}
+
+pub fn create_revision_table(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS mission_revision (
+                revision_id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+                mission_id INTEGER NOT NULL,
+                revision_number INTEGER NOT NULL CHECK (revision_number > 0),
+                revision_created TEXT DEFAULT CURRENT_TIMESTAMP NOT NULL,
+                revision_parameters TEXT NOT NULL,
+                revision_route_length REAL NOT NULL CHECK (revision_route_length >= 0),
+                revision_duration REAL NOT NULL CHECK (revision_duration >= 0),
+                revision_photo_count INTEGER NOT NULL CHECK (revision_photo_count >= 0),
+                    UNIQUE (mission_id, revision_number),
+                    FOREIGN KEY (mission_id)
+                    REFERENCES mission (mission_id)
+                    ON DELETE CASCADE
+                );
+
+        CREATE TABLE IF NOT EXISTS mission_revision_waypoint (
+                revision_id INTEGER NOT NULL,
+                waypoint_index INTEGER NOT NULL,
+                waypoint_x REAL NOT NULL,
+                waypoint_y REAL NOT NULL,
+                    PRIMARY KEY (revision_id, waypoint_index),
+                    FOREIGN KEY (revision_id)
+                    REFERENCES mission_revision (revision_id)
+                    ON DELETE CASCADE
+                );
+
+        CREATE TRIGGER IF NOT EXISTS mission_revision_immutable
+        BEFORE UPDATE ON mission_revision
+        BEGIN
+            SELECT RAISE(ABORT, 'Mission revisions are immutable');
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS mission_revision_waypoint_immutable
+        BEFORE UPDATE ON mission_revision_waypoint
+        BEGIN
+            SELECT RAISE(ABORT, 'Mission revisions are immutable');
+        END;",
+    )
+}
+
+// Should run inside a transaction, the revision number is taken from the previous revisions
+pub fn insert_revision(revision: &MissionRevision, conn: &Connection) -> Result<u64> {
+    let parameters = serde_json::to_string(&revision.parameters)
+        .map_err(|e| Error::ToSqlConversionFailure(e.into()))?;
+
+    conn.execute(
+        "INSERT INTO mission_revision (
+                mission_id,
+                revision_number,
+                revision_parameters,
+                revision_route_length,
+                revision_duration,
+                revision_photo_count
+            ) VALUES (
+                ?1,
+                (SELECT COALESCE(MAX(revision_number), 0) + 1
+                    FROM mission_revision WHERE mission_id = ?1),
+                ?2, ?3, ?4, ?5
+            )",
+        (
+            &revision.mission_id,
+            &parameters,
+            &revision.route_length,
+            &revision.duration,
+            &revision.photo_count,
+        ),
+    )?;
+
+    let revision_id = conn.last_insert_rowid() as u64;
+
+    let mut stmt = conn.prepare(
+        "INSERT INTO mission_revision_waypoint (
+                revision_id,
+                waypoint_index,
+                waypoint_x,
+                waypoint_y
+            ) VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    for (waypoint_index, (x, y)) in revision.waypoints.iter().enumerate() {
+        stmt.execute((&revision_id, waypoint_index, x, y))?;
+    }
+    Ok(revision_id)
+}
+
+const SELECT_REVISION: &str = "SELECT
+                revision_id,
+                mission_id,
+                revision_number,
+                revision_created,
+                revision_parameters,
+                revision_route_length,
+                revision_duration,
+                revision_photo_count
+            FROM mission_revision";
+
+// Waypoints are loaded separately by load_revision_waypoints
+fn revision_from_row(row: &Row) -> Result<MissionRevision> {
+    let parameters: String = row.get(4)?;
+    Ok(MissionRevision {
+        id: row.get(0)?,
+        mission_id: row.get(1)?,
+        number: row.get(2)?,
+        created: row.get(3)?,
+        parameters: serde_json::from_str::<RevisionParameters>(&parameters)
+            .map_err(|e| Error::FromSqlConversionFailure(4, Type::Text, e.into()))?,
+        waypoints: Vec::new(),
+        route_length: row.get(5)?,
+        duration: row.get(6)?,
+        photo_count: row.get(7)?,
+    })
+}
+
+fn load_revision_waypoints(revision: &mut MissionRevision, conn: &Connection) -> Result<()> {
+    let mut stmt = conn.prepare(
+        "SELECT waypoint_x, waypoint_y
+            FROM mission_revision_waypoint
+            WHERE revision_id = ?1
+            ORDER BY waypoint_index",
+    )?;
+    revision.waypoints = stmt
+        .query_map((&revision.id,), |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<Vec<(f64, f64)>>>()?;
+    Ok(())
+}
+
+pub fn get_revision(revision_id: u64, conn: &Connection) -> Result<Option<MissionRevision>> {
+    let revision = conn
+        .query_row(
+            &format!("{} WHERE revision_id = ?1", SELECT_REVISION),
+            (&revision_id,),
+            revision_from_row,
+        )
+        .optional()?;
+
+    match revision {
+        Some(mut revision) => {
+            load_revision_waypoints(&mut revision, conn)?;
+            Ok(Some(revision))
+        }
+        None => Ok(None),
+    }
+}
+
+// Revisions of the mission, oldest first
+pub fn get_revisions_vec(mission_id: u64, conn: &Connection) -> Result<Vec<MissionRevision>> {
+    let mut stmt = conn.prepare(&format!(
+        "{} WHERE mission_id = ?1 ORDER BY revision_number",
+        SELECT_REVISION
+    ))?;
+
+    let revision_iter = stmt.query_map((&mission_id,), |row| {
+        let mut revision = revision_from_row(row)?;
+        load_revision_waypoints(&mut revision, conn)?;
+        Ok(revision)
+    })?;
+
+    revision_iter.collect()
+}
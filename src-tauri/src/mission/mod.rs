@@ -0,0 +1,354 @@
+use crate::algorithms::{self, SurveyRegion};
+use crate::camera::{Camera, DEFAULT_MAX_BLUR};
+use crate::error::{Error, Result};
+use crate::job::JobContext;
+use crate::solver::{self, InputKind, SolverInput};
+use crate::uav::Uav;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+pub mod mission_handle;
+pub mod mission_sql;
+pub mod revision;
+
+// Survey mission with its parameters and the last calculated plan,
+// coordinates are in meters of the planning projection
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Mission {
+    id: u64,                            // mission id
+    pub name: String,                   // mission name
+    pub projection: String,             // planning projection, e.g. EPSG:32635
+    pub polygons: Vec<Vec<(f64, f64)>>, // survey area polygons
+    pub start_point: (f64, f64),        // takeoff and landing point
+    pub altitude: f64,                  // flight altitude in meters
+    pub overlap: f64,                   // photo overlap in percent
+    pub direction: f64,                 // discretization direction in degrees
+    pub max_blur: Option<f64>,          // share of the GSD a photo may smear, default when unset
+    pub algorithm: String,              // route solver name
+    pub uav_id: Option<u64>,            // id of the uav flying the mission
+    pub camera_id: Option<u64>,         // id of the camera taking the photos
+    pub payload_id: Option<u64>,        // payload configuration carrying the cameras
+    #[serde(default)]
+    pub regions: Vec<RegionSettings>, // per polygon overrides, by polygon index
+    #[serde(default)]
+    pub waypoints: Vec<(f64, f64)>, // calculated route, starts and ends at start_point
+    #[serde(default)]
+    pub route_length: f64, // route length in meters
+    #[serde(default)]
+    pub duration: f64, // estimated mission duration in seconds
+    #[serde(default)]
+    pub photo_count: u64, // number of photos taken
+}
+
+// Settings of one survey polygon that differ from the mission, unset ones are inherited
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct RegionSettings {
+    pub direction: Option<f64>, // discretization direction in degrees
+    pub altitude: Option<f64>,  // flight altitude in meters
+    pub overlap: Option<f64>,   // photo overlap in percent
+    pub camera_id: Option<u64>, // id of the camera taking the photos
+}
+
+// Settings of one polygon after inheriting from the mission
+pub struct RegionPlan<'a> {
+    pub direction: f64,
+    pub altitude: f64,
+    pub overlap: f64,
+    pub camera: &'a Camera,
+}
+
+// How the planned route is flown
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct FlightProfile {
+    pub ground_speed: f64,   // speed along the route in meters per second
+    pub climb_altitude: f64, // altitude climbed to before the route
+    pub speed_reduced: bool, // the camera keeps the uav below its flight speed
+}
+
+impl Mission {
+    // Settings of every polygon, region_cameras holds the cameras named by the regions
+    pub fn region_plans<'a>(
+        &self,
+        camera: &'a Camera,
+        region_cameras: &'a HashMap<u64, Camera>,
+    ) -> Result<Vec<RegionPlan<'a>>> {
+        (0..self.polygons.len())
+            .map(|index| {
+                let settings = self.regions.get(index).cloned().unwrap_or_default();
+                let camera = match settings.camera_id {
+                    Some(camera_id) => region_cameras.get(&camera_id).ok_or_else(|| {
+                        Error::validation(format!("Camera {} does not exist.", camera_id))
+                    })?,
+                    None => camera,
+                };
+                Ok(RegionPlan {
+                    direction: settings.direction.unwrap_or(self.direction),
+                    altitude: settings.altitude.unwrap_or(self.altitude),
+                    overlap: settings.overlap.unwrap_or(self.overlap),
+                    camera,
+                })
+            })
+            .collect()
+    }
+
+    // Check the parameters against the uav and camera, as the frontend does before discretizing.
+    // Every polygon is checked with its own altitude, overlap and camera.
+    pub fn check(
+        &self,
+        uav: &Uav,
+        camera: &Camera,
+        region_cameras: &HashMap<u64, Camera>,
+    ) -> Result<()> {
+        solver::find(&self.algorithm)?;
+        if self.polygons.iter().all(|polygon| polygon.len() < 3) {
+            return Err(Error::validation("Mission has no survey area."));
+        }
+        if self.max_blur.map_or(false, |max_blur| max_blur <= 0.0) {
+            return Err(Error::validation("Max blur should be greater than zero"));
+        }
+        if self.regions.len() > self.polygons.len() {
+            return Err(Error::validation(
+                "Mission has settings for polygons it does not have.",
+            ));
+        }
+        for region in self.region_plans(camera, region_cameras)? {
+            if region.altitude < uav.min_altitude || region.altitude > uav.max_altitude {
+                return Err(Error::validation("Altitude out of range"));
+            }
+            if uav.max_payload_mass < region.camera.mass {
+                return Err(Error::validation(
+                    "Uav max payload mass less then camera mass",
+                ));
+            }
+            if !(0.0..100.0).contains(&region.overlap) {
+                return Err(Error::validation(
+                    "Overlap should be between 0 and 100 percent",
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // Polygons with the photo spacing and direction of their settings
+    pub fn survey_regions(
+        &self,
+        camera: &Camera,
+        region_cameras: &HashMap<u64, Camera>,
+    ) -> Result<Vec<SurveyRegion>> {
+        Ok(self
+            .region_plans(camera, region_cameras)?
+            .iter()
+            .zip(self.polygons.iter())
+            .map(|(region, polygon)| {
+                let (photo_width, photo_height) =
+                    region.camera.photo_size(region.altitude, region.overlap);
+                SurveyRegion {
+                    polygon: polygon.clone(),
+                    photo_width,
+                    photo_height,
+                    direction_degrees: region.direction,
+                }
+            })
+            .collect())
+    }
+
+    // Photo centers of the survey area, grouped by polygon and row
+    pub fn discretize(
+        &self,
+        camera: &Camera,
+        region_cameras: &HashMap<u64, Camera>,
+    ) -> Result<Vec<Vec<Vec<(f64, f64)>>>> {
+        let input = solver::find(&self.algorithm)?.info().input;
+        algorithms::discretize_regions(
+            self.survey_regions(camera, region_cameras)?,
+            input == InputKind::Points,
+        )
+    }
+
+    // Ground speed and the altitude to climb to. The camera may not keep up with the uav,
+    // it flies slower then, one speed over all polygons so the slowest one sets it.
+    pub fn flight_profile(
+        &self,
+        uav: &Uav,
+        camera: &Camera,
+        region_cameras: &HashMap<u64, Camera>,
+    ) -> Result<FlightProfile> {
+        let mut ground_speed = uav.flight_speed;
+        let mut climb_altitude: f64 = 0.0;
+        for region in self.region_plans(camera, region_cameras)? {
+            let (_, photo_height) = region.camera.photo_size(region.altitude, region.overlap);
+            let speed_limit = region.camera.speed_limit(
+                uav.flight_speed,
+                region.altitude,
+                photo_height,
+                self.max_blur.unwrap_or(DEFAULT_MAX_BLUR),
+            );
+            ground_speed = ground_speed.min(speed_limit.ground_speed);
+            climb_altitude = climb_altitude.max(region.altitude);
+        }
+        Ok(FlightProfile {
+            ground_speed,
+            climb_altitude,
+            speed_reduced: ground_speed < uav.flight_speed,
+        })
+    }
+
+    // Discretize the area and calculate the route, replacing the previous plan.
+    // Returns how the route is flown.
+    pub fn plan(
+        &mut self,
+        uav: &Uav,
+        camera: &Camera,
+        region_cameras: &HashMap<u64, Camera>,
+        context: &JobContext,
+    ) -> Result<FlightProfile> {
+        self.check(uav, camera, region_cameras)?;
+
+        let discretized_area = self.discretize(camera, region_cameras)?;
+        let photo_count = discretized_area.iter().flatten().flatten().count();
+
+        let directions = self
+            .region_plans(camera, region_cameras)?
+            .iter()
+            .map(|region| region.direction)
+            .collect();
+        let input = SolverInput {
+            regions: discretized_area,
+            directions_degrees: directions,
+            start_point: self.start_point,
+            options: HashMap::new(),
+        };
+        let output = solver::solve(&self.algorithm, &input, context)?;
+
+        let profile = self.flight_profile(uav, camera, region_cameras)?;
+        self.route_length = output.route_length;
+        self.duration = uav.mission_duration(
+            self.route_length,
+            profile.climb_altitude,
+            profile.ground_speed,
+        );
+        self.photo_count = photo_count as u64;
+        self.waypoints = output.waypoints;
+        Ok(profile)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn camera(name: &str, fov_x: f64, exposure_time: f64) -> Camera {
+        Camera::new(name.to_string(), 300, fov_x, 4000, 3000, 0.0, exposure_time)
+    }
+
+    fn uav() -> Uav {
+        Uav::new(
+            "Test Drone".to_string(),
+            1000,
+            1800,
+            5.0,
+            20.0,
+            10.0,
+            300.0,
+            None,
+        )
+    }
+
+    fn mission(regions: Vec<RegionSettings>) -> Mission {
+        let square = vec![(0.0, 0.0), (200.0, 0.0), (200.0, 200.0), (0.0, 200.0)];
+        Mission {
+            id: 0,
+            name: "Test Mission".to_string(),
+            projection: "EPSG:32635".to_string(),
+            polygons: vec![square.clone(), square],
+            start_point: (0.0, 0.0),
+            altitude: 100.0,
+            overlap: 20.0,
+            direction: 0.0,
+            max_blur: None,
+            algorithm: "RectangularAreas".to_string(),
+            uav_id: None,
+            camera_id: None,
+            payload_id: None,
+            regions,
+            waypoints: Vec::new(),
+            route_length: 0.0,
+            duration: 0.0,
+            photo_count: 0,
+        }
+    }
+
+    #[test]
+    fn regions_inherit_unset_settings() {
+        let main_camera = camera("Main", 60.0, 0.0);
+        let region_cameras = HashMap::from([(7, camera("Wide", 90.0, 0.0))]);
+        let mission = mission(vec![RegionSettings {
+            direction: Some(45.0),
+            altitude: Some(50.0),
+            overlap: None,
+            camera_id: Some(7),
+        }]);
+
+        let plans = mission.region_plans(&main_camera, &region_cameras).unwrap();
+        assert_eq!(plans.len(), 2);
+        assert_eq!(plans[0].direction, 45.0);
+        assert_eq!(plans[0].altitude, 50.0);
+        assert_eq!(plans[0].overlap, 20.0);
+        assert_eq!(plans[0].camera.name, "Wide");
+        // The second polygon has no settings and takes all of the mission
+        assert_eq!(plans[1].direction, 0.0);
+        assert_eq!(plans[1].altitude, 100.0);
+        assert_eq!(plans[1].overlap, 20.0);
+        assert_eq!(plans[1].camera.name, "Main");
+    }
+
+    #[test]
+    fn missing_region_camera_is_rejected() {
+        let main_camera = camera("Main", 60.0, 0.0);
+        let mission = mission(vec![RegionSettings {
+            camera_id: Some(7),
+            ..RegionSettings::default()
+        }]);
+        assert!(matches!(
+            mission.region_plans(&main_camera, &HashMap::new()),
+            Err(Error::Validation(_))
+        ));
+        assert!(mission
+            .check(&uav(), &main_camera, &HashMap::new())
+            .is_err());
+    }
+
+    #[test]
+    fn slowest_region_sets_the_ground_speed() {
+        // Long exposures blur, the lower polygon has the finer GSD and the lower blur speed
+        let main_camera = camera("Main", 60.0, 1.0 / 100.0);
+        let uav = uav();
+        let mission = mission(vec![
+            RegionSettings::default(),
+            RegionSettings {
+                altitude: Some(40.0),
+                ..RegionSettings::default()
+            },
+        ]);
+
+        let profile = mission
+            .flight_profile(&uav, &main_camera, &HashMap::new())
+            .unwrap();
+        let low_limit = main_camera.speed_limit(
+            uav.flight_speed,
+            40.0,
+            main_camera.photo_size(40.0, 20.0).1,
+            DEFAULT_MAX_BLUR,
+        );
+        assert!(low_limit.reduced);
+        assert_eq!(profile.ground_speed, low_limit.ground_speed);
+        assert_eq!(profile.climb_altitude, 100.0);
+        assert!(profile.speed_reduced);
+
+        // Without blur or shutter limits the uav flies at its own speed
+        let profile = mission
+            .flight_profile(&uav, &camera("Main", 60.0, 0.0), &HashMap::new())
+            .unwrap();
+        assert_eq!(profile.ground_speed, uav.flight_speed);
+        assert!(!profile.speed_reduced);
+    }
+}
@@ -0,0 +1,144 @@
+use crate::mission::{Mission, RegionSettings};
+use crate::uav::Uav;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// Everything a plan depends on, frozen when the revision is stored
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RevisionParameters {
+    pub projection: String,             // planning projection
+    pub polygons: Vec<Vec<(f64, f64)>>, // survey area polygons
+    pub start_point: (f64, f64),        // takeoff and landing point
+    pub altitude: f64,                  // flight altitude in meters
+    pub overlap: f64,                   // photo overlap in percent
+    pub direction: f64,                 // discretization direction in degrees
+    pub max_blur: Option<f64>,          // share of the GSD a photo may smear
+    pub algorithm: String,              // route solver name
+    pub uav_id: Option<u64>,            // id of the uav flying the mission
+    pub camera_id: Option<u64>,         // id of the camera taking the photos
+    #[serde(default)]
+    pub regions: Vec<RegionSettings>,   // per polygon overrides, by polygon index
+    pub flight_speed: f64,              // uav flight speed used for the duration
+    pub takeoff_speed: f64,             // uav takeoff speed used for the duration
+}
+
+// Immutable snapshot of one mission plan
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MissionRevision {
+    pub(super) id: u64,                 // revision id
+    pub mission_id: u64,                // id of the planned mission
+    pub number: u64,                    // revision number within the mission, starting at 1
+    pub created: String,                // creation time, UTC
+    pub parameters: RevisionParameters, // parameters the plan was made with
+    pub waypoints: Vec<(f64, f64)>,     // planned route
+    pub route_length: f64,              // route length in meters
+    pub duration: f64,                  // estimated mission duration in seconds
+    pub photo_count: u64,               // number of photos taken
+}
+
+impl MissionRevision {
+    pub fn new(mission: &Mission, uav: &Uav) -> MissionRevision {
+        MissionRevision {
+            id: 0,
+            mission_id: mission.id,
+            number: 0,
+            created: String::new(),
+            parameters: RevisionParameters {
+                projection: mission.projection.clone(),
+                polygons: mission.polygons.clone(),
+                start_point: mission.start_point,
+                altitude: mission.altitude,
+                overlap: mission.overlap,
+                direction: mission.direction,
+                max_blur: mission.max_blur,
+                algorithm: mission.algorithm.clone(),
+                uav_id: mission.uav_id,
+                camera_id: mission.camera_id,
+                regions: mission.regions.clone(),
+                flight_speed: uav.flight_speed,
+                takeoff_speed: uav.takeoff_speed,
+            },
+            waypoints: mission.waypoints.clone(),
+            route_length: mission.route_length,
+            duration: mission.duration,
+            photo_count: mission.photo_count,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ParameterChange {
+    pub name: String,            // parameter name
+    pub from: serde_json::Value, // value in the older revision
+    pub to: serde_json::Value,   // value in the newer revision
+}
+
+// What changed from one revision to another
+#[derive(Debug, Serialize)]
+pub struct RevisionDiff {
+    pub from_number: u64,                        // older revision number
+    pub to_number: u64,                          // newer revision number
+    pub parameter_changes: Vec<ParameterChange>, // changed parameters
+    pub added_waypoints: Vec<(f64, f64)>,        // waypoints only in the newer revision
+    pub removed_waypoints: Vec<(f64, f64)>,      // waypoints only in the older revision
+    pub route_length_change: f64,                // meters, positive when the route got longer
+    pub duration_change: f64,                    // seconds, positive when the mission got longer
+    pub photo_count_change: i64,                 // positive when more photos are taken
+}
+
+// Waypoints closer than this are considered the same, in meters
+const WAYPOINT_TOLERANCE: f64 = 0.01;
+
+fn waypoint_key(point: &(f64, f64)) -> (i64, i64) {
+    (
+        (point.0 / WAYPOINT_TOLERANCE).round() as i64,
+        (point.1 / WAYPOINT_TOLERANCE).round() as i64,
+    )
+}
+
+// Waypoints of a missing from b, counting repeated waypoints separately
+fn missing_waypoints(a: &[(f64, f64)], b: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    let mut counts: HashMap<(i64, i64), usize> = HashMap::new();
+    for point in b {
+        *counts.entry(waypoint_key(point)).or_insert(0) += 1;
+    }
+
+    let mut missing = Vec::new();
+    for point in a {
+        match counts.get_mut(&waypoint_key(point)) {
+            Some(count) if *count > 0 => *count -= 1,
+            _ => missing.push(*point),
+        }
+    }
+    missing
+}
+
+pub fn diff(from: &MissionRevision, to: &MissionRevision) -> RevisionDiff {
+    let mut parameter_changes = Vec::new();
+    if let (Ok(serde_json::Value::Object(from_map)), Ok(serde_json::Value::Object(to_map))) = (
+        serde_json::to_value(&from.parameters),
+        serde_json::to_value(&to.parameters),
+    ) {
+        for (name, from_value) in from_map.iter() {
+            let to_value = to_map.get(name).cloned().unwrap_or_default();
+            if *from_value != to_value {
+                parameter_changes.push(ParameterChange {
+                    name: name.clone(),
+                    from: from_value.clone(),
+                    to: to_value,
+                });
+            }
+        }
+    }
+
+    RevisionDiff {
+        from_number: from.number,
+        to_number: to.number,
+        parameter_changes,
+        added_waypoints: missing_waypoints(&to.waypoints, &from.waypoints),
+        removed_waypoints: missing_waypoints(&from.waypoints, &to.waypoints),
+        route_length_change: to.route_length - from.route_length,
+        duration_change: to.duration - from.duration,
+        photo_count_change: to.photo_count as i64 - from.photo_count as i64,
+    }
+}
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+pub mod payload_handle;
+pub mod payload_sql;
+
+// Set of cameras mounted together on one uav
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Payload {
+    id: u64,                  // payload configuration id
+    pub name: String,         // configuration name
+    pub uav_id: u64,          // id of the uav carrying the payload
+    pub camera_ids: Vec<u64>, // ids of the mounted cameras
+    #[serde(default)]
+    pub total_mass: u64, // mass of all mounted cameras in grams, filled in by the database
+}
+
+impl Payload {
+    pub fn new(name: String, uav_id: u64, camera_ids: Vec<u64>) -> Payload {
+        Payload {
+            id: 0,
+            name,
+            uav_id,
+            camera_ids,
+            total_mass: 0,
+        }
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+}
@@ -0,0 +1,95 @@
+use crate::database::Database;
+use crate::error::{Error, Result};
+use crate::payload::payload_sql;
+use crate::payload::Payload;
+use crate::uav::{uav_sql, Uav};
+use rusqlite::Connection;
+use std::collections::HashSet;
+use tauri::State;
+
+// Every camera must be listed once, checked before the payload is written.
+// Returns the uav the payload is mounted on.
+pub fn check_payload(payload: &Payload, conn: &Connection) -> Result<Uav> {
+    if payload.camera_ids.is_empty() {
+        return Err(Error::validation(
+            "Payload configuration must contain at least one camera.",
+        ));
+    }
+
+    let mut listed = HashSet::new();
+    if let Some(camera_id) = payload.camera_ids.iter().find(|id| !listed.insert(*id)) {
+        return Err(Error::validation(format!(
+            "Camera {} is listed more than once.",
+            camera_id
+        )));
+    }
+
+    uav_sql::get_uav(payload.uav_id, conn)?
+        .ok_or_else(|| Error::validation(format!("UAV {} does not exist.", payload.uav_id)))
+}
+
+// The written cameras must together fit the uav max payload mass. Their mass is summed by
+// the same query that selects the valid configurations, before the transaction commits.
+pub fn check_mass(payload_id: u64, uav: &Uav, conn: &Connection) -> Result<()> {
+    let payload = payload_sql::get_payload(payload_id, conn)?.ok_or_else(|| {
+        Error::validation(format!(
+            "Payload configuration {} does not exist.",
+            payload_id
+        ))
+    })?;
+    if payload.total_mass > uav.max_payload_mass {
+        return Err(Error::validation(format!(
+            "Payload mass {} g exceeds the max payload mass {} g of UAV \"{}\".",
+            payload.total_mass, uav.max_payload_mass, uav.name
+        )));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn new_payload(payload: Payload, database: State<'_, Database>) -> Result<()> {
+    let mut conn = database.connection()?;
+    println!("Received new payload: {:?}", payload);
+    let uav = check_payload(&payload, &conn)?;
+    let tx = conn.transaction()?;
+    let payload_id = payload_sql::insert(&payload, &tx)?;
+    check_mass(payload_id, &uav, &tx)?;
+    tx.commit()?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn update_payload(payload: Payload, database: State<'_, Database>) -> Result<()> {
+    let mut conn = database.connection()?;
+    println!("Received updated payload: {:?}", payload);
+    let uav = check_payload(&payload, &conn)?;
+    let tx = conn.transaction()?;
+    payload_sql::update(&payload, &tx)?;
+    check_mass(payload.id(), &uav, &tx)?;
+    tx.commit()?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn delete_payload(payload: Payload, database: State<'_, Database>) -> Result<()> {
+    let conn = database.connection()?;
+    println!("Received delete payload: {:?}", payload);
+    payload_sql::delete(&payload, &conn)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_all_payloads_vec(database: State<'_, Database>) -> Result<Vec<Payload>> {
+    let conn = database.connection()?;
+    Ok(payload_sql::get_payloads_vec(None, false, &conn)?)
+}
+
+// Configurations the planner may use, optionally limited to one uav
+#[tauri::command]
+pub fn get_valid_payloads_vec(
+    uav_id: Option<u64>,
+    database: State<'_, Database>,
+) -> Result<Vec<Payload>> {
+    let conn = database.connection()?;
+    Ok(payload_sql::get_payloads_vec(uav_id, true, &conn)?)
+}
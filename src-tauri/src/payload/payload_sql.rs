@@ -0,0 +1,195 @@
+use crate::payload::Payload;
+use rusqlite::{Connection, OptionalExtension, Result, Row};
+
+pub fn create_table(conn: &Connection) -> Result<usize> {
+    let payload_create = conn.execute(
+        "CREATE TABLE IF NOT EXISTS payload (
+                payload_id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+                payload_name TEXT NOT NULL,
+                uav_id INTEGER NOT NULL,
+                    FOREIGN KEY (uav_id)
+                    REFERENCES uav (uav_id)
+                    ON DELETE CASCADE
+                )",
+        (),
+    );
+
+    let payload_camera_create = conn.execute(
+        "CREATE TABLE IF NOT EXISTS payload_camera (
+                payload_id INTEGER NOT NULL,
+                camera_id INTEGER NOT NULL,
+                    PRIMARY KEY (payload_id, camera_id),
+                    FOREIGN KEY (payload_id)
+                    REFERENCES payload (payload_id)
+                    ON DELETE CASCADE,
+                    FOREIGN KEY (camera_id)
+                    REFERENCES camera (camera_id)
+                    ON DELETE CASCADE
+                )",
+        (),
+    );
+
+    match (payload_create, payload_camera_create) {
+        (Ok(val1), Ok(val2)) => Ok(val1 + val2),
+        (Err(err), _) => Err(err),
+        (_, Err(err)) => Err(err),
+    }
+}
+
+// Turn every uav with a camera into a single camera payload configuration
+pub fn copy_uav_cameras(conn: &Connection) -> Result<usize> {
+    conn.execute(
+        "INSERT INTO payload (payload_name, uav_id)
+        SELECT uav_name, uav_id FROM uav
+        WHERE camera_id IS NOT NULL",
+        (),
+    )?;
+
+    conn.execute(
+        "INSERT INTO payload_camera (payload_id, camera_id)
+        SELECT payload.payload_id, uav.camera_id
+        FROM payload
+        JOIN uav ON uav.uav_id = payload.uav_id
+        WHERE uav.camera_id IS NOT NULL",
+        (),
+    )
+}
+
+fn insert_cameras(payload_id: u64, camera_ids: &[u64], conn: &Connection) -> Result<usize> {
+    let mut stmt = conn.prepare(
+        "INSERT INTO payload_camera (
+                payload_id,
+                camera_id
+            ) VALUES (?1, ?2)",
+    )?;
+
+    let mut count = 0;
+    for camera_id in camera_ids {
+        count += stmt.execute((&payload_id, camera_id))?;
+    }
+    Ok(count)
+}
+
+// Should run inside a transaction, the payload and its cameras are separate rows
+pub fn insert(payload: &Payload, conn: &Connection) -> Result<u64> {
+    conn.execute(
+        "INSERT INTO payload (
+                payload_name,
+                uav_id
+            ) VALUES (?1, ?2)",
+        (&payload.name, &payload.uav_id),
+    )?;
+
+    let payload_id = conn.last_insert_rowid() as u64;
+    insert_cameras(payload_id, &payload.camera_ids, conn)?;
+    Ok(payload_id)
+}
+
+// Should run inside a transaction, the payload and its cameras are separate rows
+pub fn update(payload: &Payload, conn: &Connection) -> Result<usize> {
+    let updated = conn.execute(
+        "
+                UPDATE payload SET
+                    payload_name = ?1,
+                    uav_id = ?2
+                WHERE payload_id = ?3",
+        (&payload.name, &payload.uav_id, &payload.id),
+    )?;
+
+    conn.execute(
+        "DELETE FROM payload_camera
+        WHERE payload_id = ?1",
+        (&payload.id,),
+    )?;
+    insert_cameras(payload.id, &payload.camera_ids, conn)?;
+    Ok(updated)
+}
+
+pub fn delete(payload: &Payload, conn: &Connection) -> Result<usize> {
+    conn.execute(
+        "DELETE FROM payload
+        WHERE payload_id = ?1",
+        (&payload.id,),
+    )
+}
+
+fn payload_from_row(row: &Row) -> Result<Payload> {
+    let camera_ids: Option<String> = row.get(3)?;
+    Ok(Payload {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        uav_id: row.get(2)?,
+        camera_ids: camera_ids
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|id| id.parse().ok())
+            .collect(),
+        total_mass: row.get(4)?,
+    })
+}
+
+// Payloads with their cameras and mass, to be grouped by payload
+const SELECT_PAYLOAD: &str = "SELECT
+                payload.payload_id,
+                payload.payload_name,
+                payload.uav_id,
+                group_concat(payload_camera.camera_id),
+                COALESCE(SUM(camera.camera_mass), 0)
+            FROM payload
+            JOIN uav ON uav.uav_id = payload.uav_id
+            LEFT JOIN payload_camera ON payload_camera.payload_id = payload.payload_id
+            LEFT JOIN camera ON camera.camera_id = payload_camera.camera_id";
+
+pub fn get_payload(payload_id: u64, conn: &Connection) -> Result<Option<Payload>> {
+    conn.query_row(
+        &format!(
+            "{}
+            WHERE payload.payload_id = ?1
+            GROUP BY payload.payload_id",
+            SELECT_PAYLOAD
+        ),
+        (&payload_id,),
+        payload_from_row,
+    )
+    .optional()
+}
+
+// Payload configurations, when only_valid is set only those within the uav max payload mass
+pub fn get_payloads(
+    uav_id: Option<u64>,
+    only_valid: bool,
+    conn: &Connection,
+) -> Result<Vec<Result<Payload>>> {
+    let mut stmt = conn.prepare(&format!(
+        "{}
+            WHERE ?1 IS NULL OR payload.uav_id = ?1
+            GROUP BY payload.payload_id
+            HAVING NOT ?2 OR (
+                COUNT(payload_camera.camera_id) > 0
+                AND COALESCE(SUM(camera.camera_mass), 0) <= uav.uav_max_payload_mass
+            )
+            ORDER BY payload.payload_id",
+        SELECT_PAYLOAD
+    ))?;
+
+    let payload_iter = stmt.query_map((&uav_id, &only_valid), payload_from_row)?;
+
+    Ok(payload_iter.collect())
+}
+
+pub fn get_payloads_vec(
+    uav_id: Option<u64>,
+    only_valid: bool,
+    conn: &Connection,
+) -> Result<Vec<Payload>> {
+    let payload_results = get_payloads(uav_id, only_valid, conn)?;
+
+    let mut payloads: Vec<Payload> = Vec::new();
+    for payload_result in payload_results {
+        match payload_result {
+            Ok(payload) => payloads.push(payload),
+            Err(err) => eprintln!("Error processing a Payload: {}", err),
+        }
+    }
+    Ok(payloads)
+}
@@ -0,0 +1,213 @@
+use crate::error::{Error, Result};
+use std::f64::consts::PI;
+
+// WGS84 ellipsoid
+const SEMI_MAJOR_AXIS: f64 = 6378137.0;
+const FLATTENING: f64 = 1.0 / 298.257223563;
+const UTM_SCALE: f64 = 0.9996;
+const UTM_FALSE_EASTING: f64 = 500000.0;
+const UTM_FALSE_NORTHING: f64 = 10000000.0;
+
+// Planning projections used by the frontend, coordinates are in meters
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    WebMercator,                   // EPSG:3857
+    Utm { zone: u8, north: bool }, // EPSG:326zz north, EPSG:327zz south
+}
+
+impl Projection {
+    // Parse an EPSG code as stored in the frontend utmZone, which names southern zones
+    // EPSG:3261zz, so both that and the standard EPSG:327zz are accepted
+    pub fn from_code(code: &str) -> Result<Projection> {
+        let number = code.trim().trim_start_matches("EPSG:");
+        let utm = |zone: &str, north: bool| match zone.parse::<u8>() {
+            Ok(zone) if (1..=60).contains(&zone) => Ok(Projection::Utm { zone, north }),
+            _ => Err(Error::validation(format!(
+                "Unsupported projection {}",
+                code
+            ))),
+        };
+
+        match number {
+            "3857" => Ok(Projection::WebMercator),
+            _ if number.len() == 6 && number.starts_with("3261") => utm(&number[4..], false),
+            _ if number.len() == 5 && number.starts_with("326") => utm(&number[3..], true),
+            _ if number.len() == 5 && number.starts_with("327") => utm(&number[3..], false),
+            _ => Err(Error::validation(format!(
+                "Unsupported projection {}",
+                code
+            ))),
+        }
+    }
+
+    // Coordinate system of a .prj file (ESRI or OGC WKT). Geographic systems give None,
+    // their coordinates are already longitude and latitude.
+    pub fn from_wkt(wkt: &str) -> Result<Option<Projection>> {
+        let wkt = wkt.trim();
+        if wkt.starts_with("GEOGCS") || wkt.starts_with("GEOGCRS") {
+            return Ok(None);
+        }
+
+        let name = wkt
+            .split('"')
+            .nth(1)
+            .unwrap_or_default()
+            .to_lowercase()
+            .replace('_', " ");
+        if let Some(index) = name.find("utm zone ") {
+            let zone = &name[index + "utm zone ".len()..];
+            let digits = zone.chars().take_while(|c| c.is_ascii_digit()).count();
+            let north = !zone[digits..].starts_with('s');
+            let code = format!(
+                "EPSG:32{}{:0>2}",
+                if north { 6 } else { 7 },
+                &zone[..digits]
+            );
+            return Projection::from_code(&code).map(Some);
+        }
+        if [
+            "pseudo-mercator",
+            "web mercator",
+            "mercator auxiliary sphere",
+        ]
+        .iter()
+        .any(|mercator| name.contains(mercator))
+        {
+            return Ok(Some(Projection::WebMercator));
+        }
+
+        // Last authority code is the one of the whole coordinate system
+        let authority = wkt
+            .rsplit("AUTHORITY[\"EPSG\",\"")
+            .next()
+            .filter(|_| wkt.contains("AUTHORITY[\"EPSG\""))
+            .and_then(|rest| rest.split('"').next())
+            .unwrap_or_default();
+        match authority {
+            "4326" => Ok(None),
+            "" => Err(Error::validation(format!(
+                "Unsupported coordinate system {}",
+                name
+            ))),
+            code => Projection::from_code(code).map(Some),
+        }
+    }
+
+    // Projection of the UTM zone containing the point, as the frontend picks it
+    pub fn utm_for(lon: f64, lat: f64) -> Projection {
+        let zone = (((lon + 180.0) / 6.0).floor() as i64 + 1).clamp(1, 60) as u8;
+        Projection::Utm {
+            zone,
+            north: lat >= 0.0,
+        }
+    }
+
+    pub fn code(&self) -> String {
+        match self {
+            Projection::WebMercator => "EPSG:3857".to_string(),
+            Projection::Utm { zone, north: true } => format!("EPSG:326{:02}", zone),
+            Projection::Utm { zone, north: false } => format!("EPSG:327{:02}", zone),
+        }
+    }
+
+    // Longitude and latitude in degrees to projected meters
+    pub fn from_lon_lat(&self, lon: f64, lat: f64) -> (f64, f64) {
+        match *self {
+            Projection::WebMercator => (
+                SEMI_MAJOR_AXIS * lon.to_radians(),
+                SEMI_MAJOR_AXIS * (PI / 4.0 + lat.to_radians() / 2.0).tan().ln(),
+            ),
+            Projection::Utm { zone, north } => utm_forward(zone, north, lon, lat),
+        }
+    }
+
+    // Projected meters to longitude and latitude in degrees
+    pub fn to_lon_lat(&self, x: f64, y: f64) -> (f64, f64) {
+        match *self {
+            Projection::WebMercator => (
+                (x / SEMI_MAJOR_AXIS).to_degrees(),
+                (2.0 * (y / SEMI_MAJOR_AXIS).exp().atan() - PI / 2.0).to_degrees(),
+            ),
+            Projection::Utm { zone, north } => utm_inverse(zone, north, x, y),
+        }
+    }
+}
+
+fn central_meridian(zone: u8) -> f64 {
+    (zone as f64 * 6.0 - 183.0).to_radians()
+}
+
+// Transverse Mercator series from Snyder, "Map Projections: A Working Manual", good to
+// millimeters inside the zone
+fn utm_forward(zone: u8, north: bool, lon: f64, lat: f64) -> (f64, f64) {
+    let e2 = FLATTENING * (2.0 - FLATTENING);
+    let ep2 = e2 / (1.0 - e2);
+    let (e4, e6) = (e2 * e2, e2 * e2 * e2);
+
+    let phi = lat.to_radians();
+    let (sin_phi, cos_phi, tan_phi) = (phi.sin(), phi.cos(), phi.tan());
+
+    let n = SEMI_MAJOR_AXIS / (1.0 - e2 * sin_phi * sin_phi).sqrt();
+    let t = tan_phi * tan_phi;
+    let c = ep2 * cos_phi * cos_phi;
+    let a = cos_phi * (lon.to_radians() - central_meridian(zone));
+    let m = SEMI_MAJOR_AXIS
+        * ((1.0 - e2 / 4.0 - 3.0 * e4 / 64.0 - 5.0 * e6 / 256.0) * phi
+            - (3.0 * e2 / 8.0 + 3.0 * e4 / 32.0 + 45.0 * e6 / 1024.0) * (2.0 * phi).sin()
+            + (15.0 * e4 / 256.0 + 45.0 * e6 / 1024.0) * (4.0 * phi).sin()
+            - (35.0 * e6 / 3072.0) * (6.0 * phi).sin());
+
+    let x = UTM_SCALE
+        * n
+        * (a + (1.0 - t + c) * a.powi(3) / 6.0
+            + (5.0 - 18.0 * t + t * t + 72.0 * c - 58.0 * ep2) * a.powi(5) / 120.0)
+        + UTM_FALSE_EASTING;
+    let y = UTM_SCALE
+        * (m + n
+            * tan_phi
+            * (a * a / 2.0
+                + (5.0 - t + 9.0 * c + 4.0 * c * c) * a.powi(4) / 24.0
+                + (61.0 - 58.0 * t + t * t + 600.0 * c - 330.0 * ep2) * a.powi(6) / 720.0));
+
+    (x, if north { y } else { y + UTM_FALSE_NORTHING })
+}
+
+fn utm_inverse(zone: u8, north: bool, x: f64, y: f64) -> (f64, f64) {
+    let e2 = FLATTENING * (2.0 - FLATTENING);
+    let ep2 = e2 / (1.0 - e2);
+    let (e4, e6) = (e2 * e2, e2 * e2 * e2);
+    let e1 = (1.0 - (1.0 - e2).sqrt()) / (1.0 + (1.0 - e2).sqrt());
+
+    let northing = if north { y } else { y - UTM_FALSE_NORTHING };
+    let m = northing / UTM_SCALE;
+    let mu = m / (SEMI_MAJOR_AXIS * (1.0 - e2 / 4.0 - 3.0 * e4 / 64.0 - 5.0 * e6 / 256.0));
+
+    let phi1 = mu
+        + (3.0 * e1 / 2.0 - 27.0 * e1.powi(3) / 32.0) * (2.0 * mu).sin()
+        + (21.0 * e1 * e1 / 16.0 - 55.0 * e1.powi(4) / 32.0) * (4.0 * mu).sin()
+        + (151.0 * e1.powi(3) / 96.0) * (6.0 * mu).sin()
+        + (1097.0 * e1.powi(4) / 512.0) * (8.0 * mu).sin();
+    let (sin_phi1, cos_phi1, tan_phi1) = (phi1.sin(), phi1.cos(), phi1.tan());
+
+    let n1 = SEMI_MAJOR_AXIS / (1.0 - e2 * sin_phi1 * sin_phi1).sqrt();
+    let t1 = tan_phi1 * tan_phi1;
+    let c1 = ep2 * cos_phi1 * cos_phi1;
+    let r1 = SEMI_MAJOR_AXIS * (1.0 - e2) / (1.0 - e2 * sin_phi1 * sin_phi1).powf(1.5);
+    let d = (x - UTM_FALSE_EASTING) / (n1 * UTM_SCALE);
+
+    let phi = phi1
+        - (n1 * tan_phi1 / r1)
+            * (d * d / 2.0
+                - (5.0 + 3.0 * t1 + 10.0 * c1 - 4.0 * c1 * c1 - 9.0 * ep2) * d.powi(4) / 24.0
+                + (61.0 + 90.0 * t1 + 298.0 * c1 + 45.0 * t1 * t1 - 252.0 * ep2 - 3.0 * c1 * c1)
+                    * d.powi(6)
+                    / 720.0);
+    let lambda = central_meridian(zone)
+        + (d - (1.0 + 2.0 * t1 + c1) * d.powi(3) / 6.0
+            + (5.0 - 2.0 * c1 + 28.0 * t1 - 3.0 * c1 * c1 + 8.0 * ep2 + 24.0 * t1 * t1)
+                * d.powi(5)
+                / 120.0)
+            / cos_phi1;
+
+    (lambda.to_degrees(), phi.to_degrees())
+}
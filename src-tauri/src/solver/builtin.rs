@@ -0,0 +1,104 @@
+use crate::algorithms;
+use crate::error::{Error, Result};
+use crate::job::JobContext;
+use crate::solver::{
+    InputKind, RouteSolver, SolverInfo, SolverInput, SolverOutput, SolverParameter,
+};
+
+pub struct NearestNeighbor;
+
+impl RouteSolver for NearestNeighbor {
+    fn info(&self) -> SolverInfo {
+        SolverInfo {
+            name: "NearestNeighbor",
+            label: "Nearest Neighbor",
+            description: "Flies to the closest photo center left, fast on any area.",
+            input: InputKind::Points,
+            slow_above: None,
+            parameters: Vec::new(),
+        }
+    }
+
+    fn solve(&self, input: &SolverInput, context: &JobContext) -> Result<SolverOutput> {
+        let points = input.points();
+        let point_count = points.len();
+        let waypoints = algorithms::nearest_neighbor_route(points, input.start_point, context)?;
+        Ok(SolverOutput::new(waypoints)?.with("point_count", point_count))
+    }
+}
+
+pub struct BruteForce;
+
+// Largest number of points brute force takes on, the search grows with their factorial.
+// The planner is warned from SLOW_ABOVE points on, scattered ones then take seconds,
+// the cap is the most that still finish within a few.
+const MAX_POINTS: SolverParameter = SolverParameter {
+    name: "max_points",
+    label: "Maximum points",
+    description: "Areas with more photo centers are refused instead of searched.",
+    default: 16.0,
+    min: 1.0,
+    max: 16.0,
+};
+
+// Photo count from which brute force takes noticeably long
+const SLOW_ABOVE: usize = 14;
+
+impl RouteSolver for BruteForce {
+    fn info(&self) -> SolverInfo {
+        SolverInfo {
+            name: "BruteForce",
+            label: "Brute Force",
+            description: "Searches for the shortest route, only for a handful of photo centers.",
+            input: InputKind::Points,
+            slow_above: Some(SLOW_ABOVE),
+            parameters: vec![MAX_POINTS],
+        }
+    }
+
+    fn check(&self, input: &SolverInput) -> Result<()> {
+        let point_count = input.regions.iter().flatten().flatten().count();
+        let max_points = input.option(&MAX_POINTS) as usize;
+        if point_count > max_points {
+            return Err(Error::validation(format!(
+                "Brute force takes at most {} points, the area has {}.",
+                max_points, point_count
+            )));
+        }
+        Ok(())
+    }
+
+    fn solve(&self, input: &SolverInput, context: &JobContext) -> Result<SolverOutput> {
+        let points = input.points();
+        let point_count = points.len();
+        let waypoints = algorithms::brute_force_route(points, input.start_point, context)?;
+        Ok(SolverOutput::new(waypoints)?
+            .with("point_count", point_count)
+            .with("optimal", true))
+    }
+}
+
+pub struct RectangularAreas;
+
+impl RouteSolver for RectangularAreas {
+    fn info(&self) -> SolverInfo {
+        SolverInfo {
+            name: "RectangularAreas",
+            label: "Rectangular Areas",
+            description: "Sweeps every polygon column by column along its direction.",
+            input: InputKind::Grids,
+            slow_above: None,
+            parameters: Vec::new(),
+        }
+    }
+
+    fn solve(&self, input: &SolverInput, context: &JobContext) -> Result<SolverOutput> {
+        let waypoints = algorithms::rectangular_route(
+            input.regions.clone(),
+            input.start_point,
+            input.directions_degrees.clone(),
+            context,
+        )?;
+        Ok(SolverOutput::new(waypoints)?.with("region_count", input.regions.len()))
+    }
+}
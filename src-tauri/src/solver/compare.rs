@@ -0,0 +1,406 @@
+use crate::algorithms::{self, SurveyRegion};
+use crate::error::{Error, Result};
+use crate::job::JobContext;
+use crate::solver::{self, InputKind, RouteSolver, SolverInfo, SolverInput, SolverOutput};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// How often the comparison looks at the time budget and the cancellation of the job
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+// Longest time budget accepted, in seconds
+const MAX_BUDGET_SECONDS: f64 = 3600.0;
+
+// Survey area and uav every solver is compared on
+#[derive(Debug, Deserialize)]
+pub struct CompareRequest {
+    pub regions: Vec<SurveyRegion>, // survey regions, discretized as every solver expects
+    pub start_point: (f64, f64),    // takeoff and landing point
+    #[serde(default)]
+    pub options: HashMap<String, HashMap<String, f64>>, // options by solver name
+    pub ground_speed: f64,          // speed the route is flown at, camera limits included
+    pub takeoff_speed: f64,         // uav takeoff speed in meters per second
+    pub climb_altitude: f64,        // altitude climbed to before the route
+    pub flight_duration: f64,       // longest flight of the uav in seconds
+    pub budget_seconds: f64,        // time all solvers together may take
+}
+
+// Whether the route of a solver can be flown
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum Verdict {
+    Feasible,      // the uav can fly the route
+    TooLong,       // the mission takes longer than the uav can fly
+    TimedOut,      // the solver found no route within the time budget
+    NotApplicable, // the solver does not take this area
+    Failed,        // the solver failed
+}
+
+// One row of the comparison
+#[derive(Debug, Serialize)]
+pub struct SolverComparison {
+    pub name: &'static str,         // solver name
+    pub label: &'static str,        // name shown in the frontend
+    pub verdict: Verdict,           // whether the route can be flown
+    pub error: Option<Error>,       // why there is no route, if there is none
+    pub photo_count: usize,         // photo centers of the discretization the solver takes
+    pub computation_time: f64,      // time the solver took in seconds
+    pub route_length: Option<f64>,  // route length in meters
+    pub duration: Option<f64>,      // estimated mission duration in seconds
+    pub waypoints: Vec<(f64, f64)>, // calculated route, empty if there is none
+}
+
+// Every solver on the same survey area, in registry order
+#[derive(Debug, Serialize)]
+pub struct Comparison {
+    pub results: Vec<SolverComparison>, // one row per solver
+    pub best: Option<String>,           // feasible solver with the shortest mission, if any
+}
+
+// Run every solver on the area at once, the ones still busy when the budget is spent
+// are stopped and left behind. Solvers taking the same kind of input share one
+// discretization, which counts towards the budget.
+pub fn compare(request: CompareRequest, context: &JobContext) -> Result<Comparison> {
+    compare_solvers(request, solver::solvers(), context)
+}
+
+// Comparison of the given solvers, in their order
+fn compare_solvers(
+    request: CompareRequest,
+    solvers: Vec<Box<dyn RouteSolver>>,
+    context: &JobContext,
+) -> Result<Comparison> {
+    if !(request.budget_seconds > 0.0 && request.budget_seconds <= MAX_BUDGET_SECONDS) {
+        return Err(Error::validation(format!(
+            "Time budget should be between 0 and {} seconds",
+            MAX_BUDGET_SECONDS
+        )));
+    }
+    if request.ground_speed <= 0.0 || request.takeoff_speed <= 0.0 {
+        return Err(Error::validation(
+            "Flight speeds should be greater than zero",
+        ));
+    }
+    let started = Instant::now();
+    let deadline = started + Duration::from_secs_f64(request.budget_seconds);
+    let directions_degrees: Vec<f64> = request
+        .regions
+        .iter()
+        .map(|region| region.direction_degrees)
+        .collect();
+
+    let mut discretized = HashMap::new();
+    let stop = Arc::new(AtomicBool::new(false));
+    let (sender, receiver) = mpsc::channel();
+    let mut results: Vec<Option<SolverComparison>> = solvers.iter().map(|_| None).collect();
+    let mut running = Vec::new(); // solvers still busy, with their input
+    for (index, solver) in solvers.into_iter().enumerate() {
+        let info = solver.info();
+        if let Entry::Vacant(entry) = discretized.entry(info.input) {
+            entry.insert(algorithms::discretize_regions(
+                request.regions.clone(),
+                info.input == InputKind::Points,
+            )?);
+        }
+        let input = Arc::new(SolverInput {
+            regions: discretized[&info.input].clone(),
+            directions_degrees: directions_degrees.clone(),
+            start_point: request.start_point,
+            options: request.options.get(info.name).cloned().unwrap_or_default(),
+        });
+        if let Err(err) = solver::check_options(&info, &input).and_then(|_| solver.check(&input)) {
+            results[index] = Some(row(&info, &input, Verdict::NotApplicable, 0.0, err));
+            continue;
+        }
+
+        let solver_context = JobContext::stoppable(Arc::clone(&stop));
+        let solver_input = Arc::clone(&input);
+        let sender = sender.clone();
+        running.push((index, info, input));
+        thread::spawn(move || {
+            let solver_started = Instant::now();
+            let result = solver.solve(&solver_input, &solver_context);
+            let computation_time = solver_started.elapsed().as_secs_f64();
+            // The receiver goes away when the comparison is over, nobody waits then
+            let _ = sender.send((index, result, computation_time));
+        });
+    }
+    // Only the solvers hold a sender now, the channel closes if one of them panics
+    drop(sender);
+
+    let total = results.len();
+    while !running.is_empty() {
+        context.report(None, (total - running.len()) as f64 * 100.0 / total as f64);
+        if context.is_cancelled() {
+            stop.store(true, Ordering::Relaxed);
+            context.check_cancelled()?;
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            break;
+        }
+        let (index, result, computation_time) =
+            match receiver.recv_timeout(POLL_INTERVAL.min(deadline - now)) {
+                Ok(finished) => finished,
+                Err(mpsc::RecvTimeoutError::Timeout) => continue,
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    stop.store(true, Ordering::Relaxed);
+                    return Err(Error::algorithm("A solver stopped without a result."));
+                }
+            };
+        let position = running
+            .iter()
+            .position(|(running_index, _, _)| *running_index == index)
+            .ok_or_else(|| Error::algorithm("A solver finished twice."))?;
+        let (_, info, input) = running.swap_remove(position);
+        results[index] = Some(match result {
+            Ok(output) => routed(&info, &input, output, computation_time, &request),
+            Err(Error::Cancelled(_)) => timed_out(&info, &input, computation_time, &request),
+            Err(err) => row(&info, &input, Verdict::Failed, computation_time, err),
+        });
+    }
+    // Solvers that missed the deadline are told to stop, their results are not waited for
+    stop.store(true, Ordering::Relaxed);
+    let computation_time = started.elapsed().as_secs_f64();
+    for (index, info, input) in running {
+        results[index] = Some(timed_out(&info, &input, computation_time, &request));
+    }
+    context.report(None, 100.0);
+
+    let results: Vec<SolverComparison> = results.into_iter().flatten().collect();
+    let best = best_solver(&results);
+    Ok(Comparison { results, best })
+}
+
+// Feasible solver with the shortest mission
+fn best_solver(results: &[SolverComparison]) -> Option<String> {
+    results
+        .iter()
+        .filter(|result| result.verdict == Verdict::Feasible)
+        .filter_map(|result| result.duration.map(|duration| (duration, result.name)))
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, name)| name.to_string())
+}
+
+// Row of a solver that returned a route, judged by the mission duration
+fn routed(
+    info: &SolverInfo,
+    input: &SolverInput,
+    output: SolverOutput,
+    computation_time: f64,
+    request: &CompareRequest,
+) -> SolverComparison {
+    let duration = algorithms::mission_duration(
+        output.route_length,
+        request.climb_altitude,
+        request.ground_speed,
+        request.takeoff_speed,
+    );
+    let verdict = if duration <= request.flight_duration {
+        Verdict::Feasible
+    } else {
+        Verdict::TooLong
+    };
+    SolverComparison {
+        name: info.name,
+        label: info.label,
+        verdict,
+        error: None,
+        photo_count: input.points().len(),
+        computation_time,
+        route_length: Some(output.route_length),
+        duration: Some(duration),
+        waypoints: output.waypoints,
+    }
+}
+
+// Row of a solver stopped by the time budget
+fn timed_out(
+    info: &SolverInfo,
+    input: &SolverInput,
+    computation_time: f64,
+    request: &CompareRequest,
+) -> SolverComparison {
+    let err = Error::cancelled(format!(
+        "No route within the time budget of {} s.",
+        request.budget_seconds
+    ));
+    row(info, input, Verdict::TimedOut, computation_time, err)
+}
+
+// Row of a solver without a route
+fn row(
+    info: &SolverInfo,
+    input: &SolverInput,
+    verdict: Verdict,
+    computation_time: f64,
+    err: Error,
+) -> SolverComparison {
+    SolverComparison {
+        name: info.name,
+        label: info.label,
+        verdict,
+        error: Some(err),
+        photo_count: input.points().len(),
+        computation_time,
+        route_length: None,
+        duration: None,
+        waypoints: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::builtin;
+
+    // Square field of side photos, one photo center per cell
+    fn request(side: usize, budget_seconds: f64, flight_duration: f64) -> CompareRequest {
+        let size = side as f64 * 20.0;
+        CompareRequest {
+            regions: vec![SurveyRegion {
+                polygon: vec![(0.0, 0.0), (size, 0.0), (size, size), (0.0, size)],
+                photo_width: 20.0,
+                photo_height: 20.0,
+                direction_degrees: 0.0,
+            }],
+            start_point: (-20.0, -20.0),
+            options: HashMap::new(),
+            ground_speed: 10.0,
+            takeoff_speed: 5.0,
+            climb_altitude: 100.0,
+            flight_duration,
+            budget_seconds,
+        }
+    }
+
+    fn verdict(comparison: &Comparison, name: &str) -> Verdict {
+        comparison
+            .results
+            .iter()
+            .find(|result| result.name == name)
+            .unwrap()
+            .verdict
+    }
+
+    #[test]
+    fn best_is_the_shortest_feasible_mission() {
+        let comparison = compare(request(3, 60.0, 3600.0), &JobContext::detached()).unwrap();
+        assert_eq!(comparison.results.len(), solver::solvers().len());
+        assert!(comparison
+            .results
+            .iter()
+            .all(|result| result.verdict == Verdict::Feasible));
+
+        let shortest = comparison
+            .results
+            .iter()
+            .filter_map(|result| result.duration)
+            .fold(f64::INFINITY, f64::min);
+        let best = comparison.best.as_deref().unwrap();
+        let best_row = comparison
+            .results
+            .iter()
+            .find(|result| result.name == best)
+            .unwrap();
+        assert_eq!(best_row.duration, Some(shortest));
+        // Brute force is exact on the photo centers nearest neighbor routes as well
+        let duration = |name| {
+            comparison
+                .results
+                .iter()
+                .find(|result| result.name == name)
+                .and_then(|result| result.duration)
+                .unwrap()
+        };
+        assert!(duration("BruteForce") <= duration("NearestNeighbor") + 1e-9);
+    }
+
+    #[test]
+    fn short_flights_are_too_long() {
+        let comparison = compare(request(3, 60.0, 10.0), &JobContext::detached()).unwrap();
+        for result in &comparison.results {
+            assert_eq!(result.verdict, Verdict::TooLong, "{}", result.name);
+            assert!(result.duration.unwrap() > 10.0);
+        }
+        assert_eq!(comparison.best, None);
+    }
+
+    #[test]
+    fn refused_solvers_are_not_applicable() {
+        let mut request = request(3, 60.0, 3600.0);
+        request.options.insert(
+            "BruteForce".to_string(),
+            HashMap::from([("max_points".to_string(), 4.0)]),
+        );
+        let comparison = compare(request, &JobContext::detached()).unwrap();
+        assert_eq!(verdict(&comparison, "BruteForce"), Verdict::NotApplicable);
+        assert_eq!(verdict(&comparison, "NearestNeighbor"), Verdict::Feasible);
+        assert_ne!(comparison.best.as_deref(), Some("BruteForce"));
+    }
+
+    #[test]
+    fn solvers_past_the_budget_time_out() {
+        let comparison = compare(request(4, 1e-9, 3600.0), &JobContext::detached()).unwrap();
+        assert_eq!(verdict(&comparison, "NearestNeighbor"), Verdict::TimedOut);
+        assert_eq!(verdict(&comparison, "RectangularAreas"), Verdict::TimedOut);
+        assert_eq!(comparison.best, None);
+        assert!(comparison
+            .results
+            .iter()
+            .all(|result| result.waypoints.is_empty()));
+    }
+
+    // Solver that never looks at the context, it returns only once the test releases it
+    struct Stubborn {
+        release: Arc<AtomicBool>,
+    }
+
+    impl RouteSolver for Stubborn {
+        fn info(&self) -> SolverInfo {
+            SolverInfo {
+                name: "Stubborn",
+                label: "Stubborn",
+                description: "Ignores the stop flag",
+                input: InputKind::Points,
+                slow_above: None,
+                parameters: Vec::new(),
+            }
+        }
+
+        fn solve(&self, input: &SolverInput, _context: &JobContext) -> Result<SolverOutput> {
+            while !self.release.load(Ordering::Relaxed) {
+                thread::sleep(Duration::from_millis(10));
+            }
+            let mut waypoints = vec![input.start_point];
+            waypoints.extend(input.points());
+            waypoints.push(input.start_point);
+            SolverOutput::new(waypoints)
+        }
+    }
+
+    #[test]
+    fn solvers_ignoring_the_stop_flag_are_not_waited_for() {
+        let release = Arc::new(AtomicBool::new(false));
+        let solvers: Vec<Box<dyn RouteSolver>> = vec![
+            Box::new(builtin::NearestNeighbor),
+            Box::new(Stubborn {
+                release: Arc::clone(&release),
+            }),
+        ];
+        let started = Instant::now();
+        let comparison =
+            compare_solvers(request(3, 0.5, 3600.0), solvers, &JobContext::detached()).unwrap();
+        let elapsed = started.elapsed();
+        release.store(true, Ordering::Relaxed);
+
+        assert!(elapsed < Duration::from_secs(2), "waited {:?}", elapsed);
+        assert_eq!(verdict(&comparison, "NearestNeighbor"), Verdict::Feasible);
+        assert_eq!(verdict(&comparison, "Stubborn"), Verdict::TimedOut);
+        assert_eq!(comparison.best.as_deref(), Some("NearestNeighbor"));
+    }
+}
@@ -0,0 +1,216 @@
+use crate::algorithms;
+use crate::error::{Error, Result};
+use crate::job::JobContext;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+pub mod builtin;
+pub mod compare;
+pub mod solver_handle;
+
+// How a solver wants the survey area discretized
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum InputKind {
+    Points, // photo centers inside the polygons, routed as one set
+    Grids,  // full columns of photo centers per polygon, routed polygon by polygon
+}
+
+// Numeric parameter a solver takes from the options, shown as an input in the frontend
+#[derive(Debug, Clone, Serialize)]
+pub struct SolverParameter {
+    pub name: &'static str,        // key in the options
+    pub label: &'static str,       // name shown in the frontend
+    pub description: &'static str, // what the parameter changes
+    pub default: f64,              // value when the option is not given
+    pub min: f64,                  // smallest accepted value
+    pub max: f64,                  // largest accepted value
+}
+
+// What the registry tells about a solver
+#[derive(Debug, Clone, Serialize)]
+pub struct SolverInfo {
+    pub name: &'static str,               // solver id, as stored in missions
+    pub label: &'static str,              // name shown in the frontend
+    pub description: &'static str,        // when to use the solver
+    pub input: InputKind,                 // discretization the solver expects
+    pub slow_above: Option<usize>,        // photo count from which the planner should be warned
+    pub parameters: Vec<SolverParameter>, // parameter schema of the options
+}
+
+// Common input of all solvers, the discretized area as from discretize_regions
+#[derive(Debug, Clone, Deserialize)]
+pub struct SolverInput {
+    pub regions: Vec<Vec<Vec<(f64, f64)>>>, // photo centers by region and column
+    pub directions_degrees: Vec<f64>,       // discretization direction of every region
+    pub start_point: (f64, f64),            // takeoff and landing point
+    #[serde(default)]
+    pub options: HashMap<String, f64>, // solver parameters by name, missing ones take defaults
+}
+
+impl SolverInput {
+    // All photo centers as one set
+    pub fn points(&self) -> Vec<(f64, f64)> {
+        self.regions.iter().flatten().flatten().copied().collect()
+    }
+
+    pub fn option(&self, parameter: &SolverParameter) -> f64 {
+        self.options
+            .get(parameter.name)
+            .copied()
+            .unwrap_or(parameter.default)
+    }
+}
+
+// Common output of all solvers
+#[derive(Debug, Clone, Serialize)]
+pub struct SolverOutput {
+    pub waypoints: Vec<(f64, f64)>, // calculated route, starts and ends at the start point
+    pub route_length: f64,          // route length in meters
+    pub metadata: BTreeMap<String, Value>, // solver specific facts about the route
+}
+
+impl SolverOutput {
+    pub fn new(waypoints: Vec<(f64, f64)>) -> Result<SolverOutput> {
+        Ok(SolverOutput {
+            route_length: algorithms::calculate_distance(waypoints.clone())?,
+            waypoints,
+            metadata: BTreeMap::new(),
+        })
+    }
+
+    pub fn with(mut self, key: &str, value: impl Into<Value>) -> SolverOutput {
+        self.metadata.insert(key.to_string(), value.into());
+        self
+    }
+}
+
+// Route algorithm the planner can pick. Solvers poll the context for cancellation and
+// report their progress through it.
+pub trait RouteSolver: Send + Sync {
+    fn info(&self) -> SolverInfo;
+
+    // Why the solver does not take the input, checked before solving
+    fn check(&self, _input: &SolverInput) -> Result<()> {
+        Ok(())
+    }
+
+    // Solvers must poll context.is_cancelled or check_cancelled while they work and return
+    // soon once it is set. A comparison stops waiting at its deadline, a solver ignoring the
+    // context keeps its thread and a core busy until it is done.
+    fn solve(&self, input: &SolverInput, context: &JobContext) -> Result<SolverOutput>;
+}
+
+// Every solver the planner can pick, in the order the frontend lists them.
+// A new algorithm only has to be added here.
+pub fn solvers() -> Vec<Box<dyn RouteSolver>> {
+    vec![
+        Box::new(builtin::NearestNeighbor),
+        Box::new(builtin::BruteForce),
+        Box::new(builtin::RectangularAreas),
+    ]
+}
+
+pub fn find(name: &str) -> Result<Box<dyn RouteSolver>> {
+    solvers()
+        .into_iter()
+        .find(|solver| solver.info().name == name)
+        .ok_or_else(|| Error::validation(format!("Unknown route algorithm {}.", name)))
+}
+
+// Check the options against the parameter schema of the solver, then solve
+pub fn solve(name: &str, input: &SolverInput, context: &JobContext) -> Result<SolverOutput> {
+    let solver = find(name)?;
+    check_options(&solver.info(), input)?;
+    solver.check(input)?;
+    solver.solve(input, context)
+}
+
+pub fn check_options(info: &SolverInfo, input: &SolverInput) -> Result<()> {
+    for (option, value) in &input.options {
+        let parameter = info
+            .parameters
+            .iter()
+            .find(|parameter| parameter.name == option)
+            .ok_or_else(|| {
+                Error::validation(format!("{} has no parameter {}.", info.label, option))
+            })?;
+        if !(parameter.min..=parameter.max).contains(value) {
+            return Err(Error::validation(format!(
+                "{} should be between {} and {}.",
+                parameter.label, parameter.min, parameter.max
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(point_count: usize, options: &[(&str, f64)]) -> SolverInput {
+        SolverInput {
+            regions: vec![vec![(0..point_count).map(|i| (i as f64, 0.0)).collect()]],
+            directions_degrees: vec![0.0],
+            start_point: (0.0, -10.0),
+            options: options
+                .iter()
+                .map(|&(name, value)| (name.to_string(), value))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn solvers_are_found_by_name() {
+        for solver in solvers() {
+            let name = solver.info().name;
+            assert_eq!(find(name).unwrap().info().name, name);
+        }
+        assert!(matches!(find("Unknown"), Err(Error::Validation(_))));
+        assert!(matches!(
+            solve("Unknown", &input(3, &[]), &JobContext::detached()),
+            Err(Error::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn options_are_checked_against_the_parameters() {
+        let info = find("BruteForce").unwrap().info();
+        assert!(check_options(&info, &input(3, &[])).is_ok());
+        assert!(check_options(&info, &input(3, &[("max_points", 1.0)])).is_ok());
+        assert!(check_options(&info, &input(3, &[("max_points", 16.0)])).is_ok());
+        for options in [
+            [("max_points", 0.0)],
+            [("max_points", 17.0)],
+            [("max_points", f64::NAN)],
+            [("min_points", 4.0)],
+        ] {
+            assert!(matches!(
+                check_options(&info, &input(3, &options)),
+                Err(Error::Validation(_))
+            ));
+        }
+
+        // Solvers without parameters take no options at all
+        let info = find("NearestNeighbor").unwrap().info();
+        assert!(matches!(
+            check_options(&info, &input(3, &[("max_points", 4.0)])),
+            Err(Error::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn brute_force_refuses_more_points_than_allowed() {
+        let context = JobContext::detached();
+        let output = solve("BruteForce", &input(4, &[("max_points", 4.0)]), &context).unwrap();
+        assert_eq!(output.waypoints.len(), 6);
+        assert!(matches!(
+            solve("BruteForce", &input(5, &[("max_points", 4.0)]), &context),
+            Err(Error::Validation(_))
+        ));
+        assert!(matches!(
+            solve("BruteForce", &input(17, &[]), &context),
+            Err(Error::Validation(_))
+        ));
+    }
+}
@@ -0,0 +1,11 @@
+use crate::solver::{self, SolverInfo};
+
+// Solvers with their parameter schemas, the frontend builds the algorithm selection from them
+#[tauri::command]
+pub fn list_solvers() -> Vec<SolverInfo> {
+    println!("Received list solvers");
+    solver::solvers()
+        .iter()
+        .map(|solver| solver.info())
+        .collect()
+}
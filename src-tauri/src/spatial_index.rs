@@ -0,0 +1,268 @@
+use crate::algorithms::euclidean_distance;
+
+// Relative slack when skipping the far side of a split, distances are rounded
+// differently than the plane offset they are compared with
+const PRUNE_TOLERANCE: f64 = 1e-12;
+
+// Static k-d tree over a point set that supports removing points, for repeated nearest
+// point queries. Nodes are implicit: a range of the layout is split at its middle element,
+// the left part lies below it and the right part above it along the axis of the depth.
+pub struct KdTree<'a> {
+    points: &'a [(f64, f64)],
+    layout: Vec<usize>,   // point indices in tree order
+    position: Vec<usize>, // position of every point index in the layout
+    alive: Vec<usize>,    // points left in the subtree of every layout position
+    removed: Vec<bool>,   // removed flag of every point index
+}
+
+impl<'a> KdTree<'a> {
+    pub fn new(points: &'a [(f64, f64)]) -> KdTree<'a> {
+        let mut layout: Vec<usize> = (0..points.len()).collect();
+        build(points, &mut layout, 0);
+
+        let mut position = vec![0; points.len()];
+        for (layout_position, &index) in layout.iter().enumerate() {
+            position[index] = layout_position;
+        }
+        let mut alive = vec![0; points.len()];
+        count_subtrees(&mut alive, 0, points.len());
+
+        KdTree {
+            points,
+            layout,
+            position,
+            alive,
+            removed: vec![false; points.len()],
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if self.removed[index] {
+            return;
+        }
+        self.removed[index] = true;
+
+        let target = self.position[index];
+        let (mut low, mut high) = (0, self.layout.len());
+        while low < high {
+            let middle = low + (high - low) / 2;
+            self.alive[middle] -= 1;
+            if target == middle {
+                break;
+            } else if target < middle {
+                high = middle;
+            } else {
+                low = middle + 1;
+            }
+        }
+    }
+
+    // Index of the closest point left. Equally close points go to the lowest index,
+    // the same one a linear scan in index order finds.
+    pub fn nearest(&self, query: &(f64, f64)) -> Option<usize> {
+        let mut best = None;
+        self.search(query, 0, self.layout.len(), 0, &mut best);
+        best.map(|(_, index)| index)
+    }
+
+    fn search(
+        &self,
+        query: &(f64, f64),
+        low: usize,
+        high: usize,
+        depth: usize,
+        best: &mut Option<(f64, usize)>,
+    ) {
+        if low >= high {
+            return;
+        }
+        let middle = low + (high - low) / 2;
+        if self.alive[middle] == 0 {
+            return;
+        }
+
+        let index = self.layout[middle];
+        let point = self.points[index];
+        if !self.removed[index] {
+            let distance = euclidean_distance(query, &point);
+            let closer = match *best {
+                Some((best_distance, best_index)) => {
+                    distance < best_distance || (distance == best_distance && index < best_index)
+                }
+                None => true,
+            };
+            if closer {
+                *best = Some((distance, index));
+            }
+        }
+
+        let offset = axis(query, depth) - axis(&point, depth);
+        let (near, far) = if offset < 0.0 {
+            ((low, middle), (middle + 1, high))
+        } else {
+            ((middle + 1, high), (low, middle))
+        };
+        self.search(query, near.0, near.1, depth + 1, best);
+        let reachable = match *best {
+            Some((best_distance, _)) => offset.abs() <= best_distance * (1.0 + PRUNE_TOLERANCE),
+            None => true,
+        };
+        if reachable {
+            self.search(query, far.0, far.1, depth + 1, best);
+        }
+    }
+}
+
+fn axis(point: &(f64, f64), depth: usize) -> f64 {
+    if depth % 2 == 1 {
+        point.1
+    } else {
+        point.0
+    }
+}
+
+// Put the median of the range along the depth axis in the middle, then the halves
+fn build(points: &[(f64, f64)], layout: &mut [usize], depth: usize) {
+    if layout.len() <= 1 {
+        return;
+    }
+    let middle = layout.len() / 2;
+    layout.select_nth_unstable_by(middle, |&a, &b| {
+        axis(&points[a], depth)
+            .total_cmp(&axis(&points[b], depth))
+            .then(a.cmp(&b))
+    });
+    let (left, right) = layout.split_at_mut(middle);
+    build(points, left, depth + 1);
+    build(points, &mut right[1..], depth + 1);
+}
+
+fn count_subtrees(alive: &mut [usize], low: usize, high: usize) {
+    if low >= high {
+        return;
+    }
+    let middle = low + (high - low) / 2;
+    alive[middle] = high - low;
+    count_subtrees(alive, low, middle);
+    count_subtrees(alive, middle + 1, high);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    // Closest point left by a scan in index order, the first of equally close ones wins
+    fn linear_nearest(
+        points: &[(f64, f64)],
+        removed: &[bool],
+        query: &(f64, f64),
+    ) -> Option<usize> {
+        let mut best: Option<(f64, usize)> = None;
+        for (index, point) in points.iter().enumerate() {
+            if removed[index] {
+                continue;
+            }
+            let distance = euclidean_distance(query, point);
+            if best.map_or(true, |(best_distance, _)| distance < best_distance) {
+                best = Some((distance, index));
+            }
+        }
+        best.map(|(_, index)| index)
+    }
+
+    // Remove the points in the given order, comparing every query against the scan on the way
+    fn assert_matches_linear_scan(
+        points: &[(f64, f64)],
+        queries: &[(f64, f64)],
+        removals: &[usize],
+    ) {
+        let mut tree = KdTree::new(points);
+        let mut removed = vec![false; points.len()];
+        for step in 0..=removals.len() {
+            for query in queries {
+                assert_eq!(
+                    tree.nearest(query),
+                    linear_nearest(points, &removed, query),
+                    "query {:?} after {} removals",
+                    query,
+                    step
+                );
+            }
+            if let Some(&index) = removals.get(step) {
+                tree.remove(index);
+                removed[index] = true;
+            }
+        }
+    }
+
+    fn lattice(columns: usize, rows: usize, spacing: f64) -> Vec<(f64, f64)> {
+        (0..columns * rows)
+            .map(|i| {
+                (
+                    (i % columns) as f64 * spacing,
+                    (i / columns) as f64 * spacing,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn empty_tree_has_no_nearest_point() {
+        let mut tree = KdTree::new(&[(1.0, 1.0)]);
+        tree.remove(0);
+        tree.remove(0);
+        assert_eq!(tree.nearest(&(0.0, 0.0)), None);
+        assert_eq!(KdTree::new(&[]).nearest(&(0.0, 0.0)), None);
+    }
+
+    #[test]
+    fn duplicate_points_go_to_the_lowest_index() {
+        let points = vec![(5.0, 5.0), (1.0, 1.0), (5.0, 5.0), (1.0, 1.0), (5.0, 5.0)];
+        let queries = vec![(5.0, 5.0), (1.0, 1.0), (3.0, 3.0), (0.0, 0.0)];
+        assert_matches_linear_scan(&points, &queries, &[0, 1, 4, 3, 2]);
+    }
+
+    #[test]
+    fn collinear_points_match_the_scan() {
+        let horizontal: Vec<(f64, f64)> = (0..20).map(|i| (i as f64 * 3.0, 7.0)).collect();
+        let diagonal: Vec<(f64, f64)> = (0..20).map(|i| (i as f64, i as f64)).rev().collect();
+        let queries: Vec<(f64, f64)> = (0..40)
+            .map(|i| (i as f64 * 1.5 - 3.0, (i % 5) as f64 * 2.0))
+            .collect();
+        let removals: Vec<usize> = (0..20).map(|i| (i * 7) % 20).collect();
+        assert_matches_linear_scan(&horizontal, &queries, &removals);
+        assert_matches_linear_scan(&diagonal, &queries, &removals);
+    }
+
+    #[test]
+    fn lattice_ties_match_the_scan() {
+        // Queries between lattice points are equally close to two or four of them
+        let points = lattice(6, 6, 10.0);
+        let queries: Vec<(f64, f64)> = (0..13)
+            .flat_map(|i| (0..13).map(move |j| (i as f64 * 5.0 - 5.0, j as f64 * 5.0 - 5.0)))
+            .collect();
+        let removals: Vec<usize> = (0..36).map(|i| (i * 11) % 36).collect();
+        assert_matches_linear_scan(&points, &queries, &removals);
+    }
+
+    proptest! {
+        #[test]
+        fn interleaved_removals_match_the_scan(
+            // Few distinct coordinates make duplicates and ties common
+            points in prop::collection::vec((0..6, 0..6), 1..40),
+            queries in prop::collection::vec((-1.0..7.0, -1.0..7.0), 1..10),
+            removal_seeds in prop::collection::vec(any::<usize>(), 0..40),
+        ) {
+            let points: Vec<(f64, f64)> = points
+                .into_iter()
+                .map(|(x, y)| (x as f64, y as f64))
+                .collect();
+            let removals: Vec<usize> = removal_seeds
+                .iter()
+                .map(|seed| seed % points.len())
+                .collect();
+            assert_matches_linear_scan(&points, &queries, &removals);
+        }
+    }
+}
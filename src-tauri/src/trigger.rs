@@ -0,0 +1,220 @@
+use crate::algorithms::{
+    coordinate_restore, coordinate_transformation, euclidean_distance, SurveyRegion,
+};
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::f64::{INFINITY, NEG_INFINITY};
+
+// Continuous flight line with the camera triggered by distance from start to stop
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TriggerLine {
+    pub polygon: usize,        // index of the survey polygon the line belongs to
+    pub start: (f64, f64),     // first trigger position
+    pub stop: (f64, f64),      // last trigger position
+    pub trigger_distance: f64, // meters between triggers, photo footprint minus front-lap
+    pub photo_count: u64,      // triggers on the line, start and stop included
+}
+
+// Distance based camera triggering for a whole survey, lines in flying order
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TriggerPlan {
+    pub lines: Vec<TriggerLine>,
+    pub trigger_interval: Option<f64>, // shortest seconds between triggers at the flight speed
+    pub photo_count: u64,              // exact number of photos of the survey
+    pub route_length: f64,             // from the start point along every line and back
+}
+
+// Flight lines of the discretization grid clipped to the polygons. Lines run along the
+// columns of discretize_area, photo_height apart from each other is the front-lap spacing.
+// Every other column is flown backwards, so columns alternate like a lawnmower, and the
+// parts of a column split by a concave edge are flown one after the other the same way.
+// The lines replace the planned route, their own length is returned with them.
+#[tauri::command]
+pub fn camera_trigger_plan(
+    polygons: Vec<Vec<(f64, f64)>>,
+    photo_width: f64,
+    photo_height: f64,
+    direction_degrees: f64,
+    start_point: (f64, f64),
+    flight_speed: Option<f64>,
+) -> Result<TriggerPlan> {
+    let regions = polygons
+        .into_iter()
+        .map(|polygon| SurveyRegion {
+            polygon,
+            photo_width,
+            photo_height,
+            direction_degrees,
+        })
+        .collect();
+    region_trigger_plan(regions, start_point, flight_speed)
+}
+
+// Same as camera_trigger_plan with photo size and direction set per polygon
+#[tauri::command]
+pub fn region_trigger_plan(
+    regions: Vec<SurveyRegion>,
+    start_point: (f64, f64),
+    flight_speed: Option<f64>,
+) -> Result<TriggerPlan> {
+    println!("Received camera trigger plan: {} polygons", regions.len());
+    let mut lines = Vec::new();
+    let mut flown_columns = 0;
+
+    for (polygon_index, region) in regions.iter().enumerate() {
+        let (photo_width, photo_height) = (region.photo_width, region.photo_height);
+        if photo_width <= 0.0 || photo_height <= 0.0 {
+            return Err(Error::validation("Photo size should be greater than zero"));
+        }
+        if region.polygon.len() < 3 {
+            continue;
+        }
+
+        let direction_radians = region.direction_degrees.to_radians();
+        let polygon: Vec<(f64, f64)> = region
+            .polygon
+            .iter()
+            .map(|&(x, y)| coordinate_transformation(x, y, direction_radians))
+            .collect();
+        let (min_x, max_x) = polygon
+            .iter()
+            .fold((INFINITY, NEG_INFINITY), |(min, max), p| {
+                (min.min(p.0), max.max(p.0))
+            });
+
+        // Same columns as discretize_area
+        let column_count = ((max_x - min_x) / photo_width) as u64 + 1;
+        for column in 0..column_count {
+            let x = min_x + (column as f64 + 0.5) * photo_width;
+            let mut intervals = column_intervals(&polygon, x);
+            if intervals.is_empty() {
+                continue;
+            }
+            let backwards = flown_columns % 2 == 1;
+            flown_columns += 1;
+            if backwards {
+                intervals.reverse();
+            }
+
+            for (y_start, y_stop) in intervals {
+                // Stretch the line to a whole number of triggers so the far edge is covered
+                let trigger_count = ((y_stop - y_start) / photo_height).ceil();
+                let (mut start, mut stop) = (y_start, y_start + trigger_count * photo_height);
+                if backwards {
+                    (start, stop) = (stop, start);
+                }
+
+                lines.push(TriggerLine {
+                    polygon: polygon_index,
+                    start: coordinate_restore(x, start, direction_radians),
+                    stop: coordinate_restore(x, stop, direction_radians),
+                    trigger_distance: photo_height,
+                    photo_count: trigger_count as u64 + 1,
+                });
+            }
+        }
+    }
+
+    // The camera has to keep up with the shortest trigger distance
+    let shortest_distance = lines
+        .iter()
+        .map(|line| line.trigger_distance)
+        .fold(INFINITY, f64::min);
+    Ok(TriggerPlan {
+        trigger_interval: flight_speed
+            .filter(|speed| *speed > 0.0 && shortest_distance.is_finite())
+            .map(|speed| shortest_distance / speed),
+        photo_count: lines.iter().map(|line| line.photo_count).sum(),
+        route_length: route_length(&lines, start_point),
+        lines,
+    })
+}
+
+// Takeoff at the start point, every line from start to stop in order, then back
+fn route_length(lines: &[TriggerLine], start_point: (f64, f64)) -> f64 {
+    let mut length = 0.0;
+    let mut last = start_point;
+    for line in lines {
+        length +=
+            euclidean_distance(&last, &line.start) + euclidean_distance(&line.start, &line.stop);
+        last = line.stop;
+    }
+    length + euclidean_distance(&last, &start_point)
+}
+
+// Parts of the vertical line at x inside the polygon, from bottom to top.
+// Touching intervals are joined, so bridges to holes do not split a line.
+fn column_intervals(polygon: &[(f64, f64)], x: f64) -> Vec<(f64, f64)> {
+    let mut crossings: Vec<f64> = polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .filter(|(a, b)| (a.0 > x) != (b.0 > x))
+        .map(|(a, b)| a.1 + (x - a.0) * (b.1 - a.1) / (b.0 - a.0))
+        .collect();
+    crossings.sort_by(f64::total_cmp);
+
+    let mut intervals: Vec<(f64, f64)> = Vec::new();
+    for pair in crossings.chunks_exact(2) {
+        match intervals.last_mut() {
+            Some(last) if (pair[0] - last.1).abs() < 1e-9 => last.1 = pair[1],
+            _ => intervals.push((pair[0], pair[1])),
+        }
+    }
+    intervals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns_alternate_and_keep_their_direction_past_a_notch() {
+        // Horseshoe open to the right, the columns past its back cross both arms
+        let horseshoe = vec![
+            (0.0, 0.0),
+            (100.0, 0.0),
+            (100.0, 25.0),
+            (40.0, 25.0),
+            (40.0, 75.0),
+            (100.0, 75.0),
+            (100.0, 100.0),
+            (0.0, 100.0),
+        ];
+        let plan =
+            camera_trigger_plan(vec![horseshoe], 20.0, 10.0, 0.0, (-10.0, -10.0), None).unwrap();
+
+        let mut columns: Vec<(f64, Vec<&TriggerLine>)> = Vec::new();
+        for line in &plan.lines {
+            match columns.last_mut() {
+                Some((x, column)) if (*x - line.start.0).abs() < 1e-9 => column.push(line),
+                _ => columns.push((line.start.0, vec![line])),
+            }
+        }
+        assert_eq!(columns.len(), 5);
+        for (index, (_, column)) in columns.iter().enumerate() {
+            let upwards = index % 2 == 0;
+            for line in column {
+                assert_eq!(line.stop.1 > line.start.1, upwards);
+            }
+            // A split column is flown as one pass, its parts follow each other
+            for pair in column.windows(2) {
+                assert_eq!(pair[1].start.1 > pair[0].stop.1, upwards);
+            }
+        }
+        assert!(columns.iter().any(|(_, column)| column.len() == 2));
+    }
+
+    #[test]
+    fn route_length_follows_the_lines() {
+        let square = vec![(0.0, 0.0), (40.0, 0.0), (40.0, 30.0), (0.0, 30.0)];
+        let plan = camera_trigger_plan(vec![square], 20.0, 10.0, 0.0, (0.0, 0.0), None).unwrap();
+        // Up the column at x 10, over to x 30, down it and back to the start
+        assert_eq!(plan.lines.len(), 2);
+        let expected = 10.0 + 30.0 + 20.0 + 30.0 + 30.0;
+        assert!(
+            (plan.route_length - expected).abs() < 1e-9,
+            "{}",
+            plan.route_length
+        );
+    }
+}
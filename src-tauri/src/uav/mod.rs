@@ -1,3 +1,4 @@
+use crate::algorithms;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 pub mod uav_handle;
@@ -62,6 +63,11 @@ impl Uav {
         )
     }
 
+    // Estimated mission duration in seconds with the takeoff speed of the uav
+    pub fn mission_duration(&self, route_length: f64, altitude: f64, ground_speed: f64) -> f64 {
+        algorithms::mission_duration(route_length, altitude, ground_speed, self.takeoff_speed)
+    }
+
     pub fn print_uav(&self) {
         println!("id: {}", &self.id);
         println!("name: {}", &self.name);
@@ -1,42 +1,54 @@
+use crate::camera::camera_sql;
+use crate::database::Database;
+use crate::error::{Error, Result};
 use crate::uav::uav_sql;
 use crate::uav::Uav;
 use rusqlite::Connection;
+use tauri::State;
+
+// The default camera must exist and fit the uav max payload mass
+fn check_camera(uav: &Uav, conn: &Connection) -> Result<()> {
+    if let Some(camera_id) = uav.camera_id {
+        let camera = camera_sql::get_camera(camera_id, conn)?
+            .ok_or_else(|| Error::validation(format!("Camera {} does not exist.", camera_id)))?;
+        if camera.mass > uav.max_payload_mass {
+            return Err(Error::validation(format!(
+                "Camera \"{}\" mass {} g exceeds the max payload mass {} g.",
+                camera.name, camera.mass, uav.max_payload_mass
+            )));
+        }
+    }
+    Ok(())
+}
 
 #[tauri::command]
-pub fn new_uav(uav: Uav) -> String {
-    let conn = Connection::open("mydatabase.db").expect("Cant open base");
+pub fn new_uav(uav: Uav, database: State<'_, Database>) -> Result<()> {
+    let conn = database.connection()?;
     println!("Received new UAV: {:?}", uav);
-    match uav_sql::insert(&uav, &conn) {
-        Ok(_) => "Ok".to_string(),
-        Err(e) => e.to_string(),
-    }
+    check_camera(&uav, &conn)?;
+    uav_sql::insert(&uav, &conn)?;
+    Ok(())
 }
 
 #[tauri::command]
-pub fn update_uav(uav: Uav) -> String {
-    let conn = Connection::open("mydatabase.db").expect("Cant open base");
+pub fn update_uav(uav: Uav, database: State<'_, Database>) -> Result<()> {
+    let conn = database.connection()?;
     println!("Received updated UAV: {:?}", uav);
-    match uav_sql::update(&uav, &conn) {
-        Ok(_) => "Ok".to_string(),
-        Err(e) => e.to_string(),
-    }
+    check_camera(&uav, &conn)?;
+    uav_sql::update(&uav, &conn)?;
+    Ok(())
 }
 
 #[tauri::command]
-pub fn delete_uav(uav: Uav) -> String {
-    let conn = Connection::open("mydatabase.db").expect("Cant open base");
+pub fn delete_uav(uav: Uav, database: State<'_, Database>) -> Result<()> {
+    let conn = database.connection()?;
     println!("Received delete UAV: {:?}", uav);
-    match uav_sql::delete(&uav, &conn) {
-        Ok(_) => "Ok".to_string(),
-        Err(e) => e.to_string(),
-    }
+    uav_sql::delete(&uav, &conn)?;
+    Ok(())
 }
 
 #[tauri::command]
-pub fn get_all_uavs_vec() -> Vec<Uav> {
-    let conn = Connection::open("mydatabase.db").expect("Cant open base");
-    match uav_sql::get_uavs_vec(&conn) {
-        Ok(result) => result,
-        Err(_) => vec![],
-    }
+pub fn get_all_uavs_vec(database: State<'_, Database>) -> Result<Vec<Uav>> {
+    let conn = database.connection()?;
+    Ok(uav_sql::get_uavs_vec(&conn)?)
 }
@@ -1,5 +1,5 @@
 use crate::uav::Uav;
-use rusqlite::{Connection, Result};
+use rusqlite::{Connection, OptionalExtension, Result, Row};
 
 pub fn create_table(conn: &Connection) -> Result<usize> {
     let db_create = conn.execute(
@@ -93,9 +93,17 @@ pub fn delete(uav: &Uav, conn: &Connection) -> Result<usize> {
     )
 }
 
-pub fn get_uavs(conn: &Connection) -> Result<Vec<Result<Uav>>> {
-    let mut stmt = conn.prepare(
-        "SELECT
+// Detach cameras that no longer exist, left behind while foreign keys were not enforced
+pub fn clear_dangling_cameras(conn: &Connection) -> Result<usize> {
+    conn.execute(
+        "UPDATE uav SET camera_id = NULL
+        WHERE camera_id IS NOT NULL
+            AND camera_id NOT IN (SELECT camera_id FROM camera)",
+        (),
+    )
+}
+
+const SELECT_UAV: &str = "SELECT
                     uav_id,
                     uav_name,
                     uav_max_payload_mass,
@@ -105,22 +113,48 @@ pub fn get_uavs(conn: &Connection) -> Result<Vec<Result<Uav>>> {
                     uav_min_altitude,
                     uav_max_altitude,
                     camera_id
-                FROM uav",
-    )?;
-
-    let uav_iter = stmt.query_map([], |row| {
-        Ok(Uav {
-            id: row.get(0)?,
-            name: row.get(1)?,
-            max_payload_mass: row.get(2)?,
-            flight_duration: row.get(3)?,
-            takeoff_speed: row.get(4)?,
-            flight_speed: row.get(5)?,
-            min_altitude: row.get(6)?,
-            max_altitude: row.get(7)?,
-            camera_id: row.get(8)?,
-        })
-    })?;
+                FROM uav";
+
+fn uav_from_row(row: &Row) -> Result<Uav> {
+    Ok(Uav {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        max_payload_mass: row.get(2)?,
+        flight_duration: row.get(3)?,
+        takeoff_speed: row.get(4)?,
+        flight_speed: row.get(5)?,
+        min_altitude: row.get(6)?,
+        max_altitude: row.get(7)?,
+        camera_id: row.get(8)?,
+    })
+}
+
+// Store speeds measured on a real flight
+pub fn update_speeds(
+    uav_id: u64,
+    flight_speed: f64,
+    takeoff_speed: f64,
+    conn: &Connection,
+) -> Result<usize> {
+    conn.execute(
+        "UPDATE uav SET uav_flight_speed = ?1, uav_takeoff_speed = ?2 WHERE uav_id = ?3",
+        (&flight_speed, &takeoff_speed, &uav_id),
+    )
+}
+
+pub fn get_uav(uav_id: u64, conn: &Connection) -> Result<Option<Uav>> {
+    conn.query_row(
+        &format!("{} WHERE uav_id = ?1", SELECT_UAV),
+        (&uav_id,),
+        uav_from_row,
+    )
+    .optional()
+}
+
+pub fn get_uavs(conn: &Connection) -> Result<Vec<Result<Uav>>> {
+    let mut stmt = conn.prepare(SELECT_UAV)?;
+
+    let uav_iter = stmt.query_map([], uav_from_row)?;
 
     Ok(uav_iter.collect())
 }
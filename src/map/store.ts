@@ -1,4 +1,4 @@
-import { writable } from 'svelte/store';
+import { derived, writable } from 'svelte/store';
 export interface Camera {
 	id: number;
 	name: string;
@@ -6,6 +6,17 @@ export interface Camera {
 	fov_x: number;
 	resolution_x: number;
 	resolution_y: number;
+	min_shutter_interval: number;
+	exposure_time: number;
+}
+
+// Ground speed the camera allows, see capture_speed_limit
+export interface SpeedLimit {
+	flight_speed: number;
+	blur_speed: number | null;
+	shutter_speed: number | null;
+	ground_speed: number;
+	reduced: boolean;
 }
 
 export interface Uav {
@@ -20,6 +31,111 @@ export interface Uav {
 	camera_id: number | null;
 }
 
+// Settings of one polygon that differ from the mission, null ones are inherited
+export interface RegionSettings {
+	direction: number | null;
+	altitude: number | null;
+	overlap: number | null;
+	camera_id: number | null;
+}
+
+// Polygon with its own photo spacing and direction, see discretize_regions
+export interface SurveyRegion {
+	polygon: number[][];
+	photo_width: number;
+	photo_height: number;
+	direction_degrees: number;
+}
+
+// Survey region with the camera and altitude its photos are taken with
+export interface RegionPlan {
+	survey: SurveyRegion;
+	camera: Camera;
+	altitude: number;
+}
+
+// Distance based camera triggering, lines in flying order
+export interface TriggerLine {
+	polygon: number;
+	start: number[];
+	stop: number[];
+	trigger_distance: number;
+	photo_count: number;
+}
+
+export interface TriggerPlan {
+	lines: TriggerLine[];
+	trigger_interval: number | null;
+	photo_count: number;
+	route_length: number;
+}
+
+// Progress of a route job, received as the job-progress event
+export interface JobProgress {
+	job_id: number;
+	best_length: number | null;
+	percent: number;
+}
+
+// Numeric option of a route solver
+export interface SolverParameter {
+	name: string;
+	label: string;
+	description: string;
+	default: number;
+	min: number;
+	max: number;
+}
+
+// Route solver as listed by list_solvers
+export interface SolverInfo {
+	name: string;
+	label: string;
+	description: string;
+	input: 'Points' | 'Grids';
+	slow_above: number | null;
+	parameters: SolverParameter[];
+}
+
+// One solver of a comparison, see compare in the solver module
+export interface SolverComparison {
+	name: string;
+	label: string;
+	verdict: 'Feasible' | 'TooLong' | 'TimedOut' | 'NotApplicable' | 'Failed';
+	error: CommandError | null;
+	photo_count: number;
+	computation_time: number;
+	route_length: number | null;
+	duration: number | null;
+	waypoints: number[][];
+}
+
+// State of a route, compare or plan job as returned by get_job_result
+export type JobStatus =
+	| { state: 'Running' }
+	| {
+			state: 'Finished';
+			waypoints: number[][];
+			route_length: number;
+			metadata: Record<string, unknown>;
+	  }
+	| { state: 'Compared'; results: SolverComparison[]; best: string | null }
+	| { state: 'Planned'; mission_id: number; ground_speed: number; speed_reduced: boolean }
+	| { state: 'Cancelled' }
+	| { state: 'Failed'; error: CommandError };
+
+// Error returned by every tauri command
+export interface CommandError {
+	kind: 'Database' | 'Validation' | 'Algorithm' | 'File' | 'Cancelled';
+	message: string;
+}
+
+export function errorMessage(error: unknown): string {
+	const commandError = error as CommandError;
+	return commandError?.message !== undefined
+		? `${commandError.kind}: ${commandError.message}`
+		: String(error);
+}
 
 export const selectedUav = writable<Uav | null>(null);
 export const selectedCamera = writable<Camera | null>(null);
@@ -27,18 +143,24 @@ export const selectedCamera = writable<Camera | null>(null);
 export const altitudeValue = writable<number>(0);
 export const overlapValue = writable<number>(0);
 
-export enum Algorithm {
-	NearestNeighbor = "NearestNeighbor",
-	BruteForce = "BruteForce",
-	RectangularAreas = "RectangularAreas"
-}
-export const selectedAlgorithm = writable<Algorithm>(Algorithm.NearestNeighbor);
+export const solvers = writable<SolverInfo[]>([]);
+export const selectedAlgorithm = writable<string>('NearestNeighbor');
+export const solverOptions = writable<Record<string, number>>({});
+// Solver picked in the algorithm selection, null until the solvers are listed
+export const selectedSolver = derived(
+	[solvers, selectedAlgorithm],
+	([$solvers, $selectedAlgorithm]) =>
+		$solvers.find((solver) => solver.name === $selectedAlgorithm) ?? null
+);
 
 export const utmZone = writable<string>('EPSG:3857');
 export const planInMeters = writable<number[][]>([]);
 
 export const routeLength = writable<number>(0);
 export const missionDuration = writable<number>(0);
+// Duration when the trigger plan lines are flown instead of the route
+export const triggerDuration = writable<number>(0);
+export const groundSpeed = writable<number | null>(null);
 export const photoCount = writable<number>(0);
 
 
@@ -119,6 +241,11 @@ export const isDrawing = writable<Boolean>(false);
 
 export const discretizationDirection = writable<number>(0);
 
+export const areaInMeters = writable<number[][][]>([]);
+export const photoSize = writable<number[]>([0, 0]);
+export const regionSettings = writable<RegionSettings[]>([]);
+export const regionPlans = writable<RegionPlan[]>([]);
+export const triggerPlan = writable<TriggerPlan | null>(null);
 export const discretizedArea = writable<number[][][][]>([]);
 export const startingPoint = writable<number[] | null>(null);
 export const planResult = writable<number[][]>([]);